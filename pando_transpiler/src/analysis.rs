@@ -0,0 +1,503 @@
+use crate::error::{TranspilerError, Diagnostics};
+use crate::types::{Span, ParsedLine, Expression, FormatPart, ForIterable, Block, is_mutating_method, is_mutable_collection, list_element_type};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// Переменная, видимая в области видимости, и её состояние в текущей точке программы
+struct Binding<'a> {
+    name: String,
+    // Флаг mut в объявлении переменной, переменной цикла или параметра
+    mutable: &'a mut bool,
+    // Место объявления
+    span: Span,
+    // Значение присвоено на всех путях выполнения до текущей точки
+    initialized: bool,
+    // Значение могло быть присвоено хотя бы на одном пути (повторное присваивание требует mut)
+    maybe_initialized: bool,
+    // Значение переменной где-либо читается
+    used: bool,
+    // Сообщать ли, что переменная не используется (для параметров функций — нет)
+    warn_unused: bool,
+    // Копии коллекций, связанные с переменной (она сама копия или с неё снята копия)
+    copies: Vec<SharedCopy>,
+}
+
+// Коллекция, которая в Python была бы общей для двух имён, а здесь копируется.
+// Предупреждение выводится при первом изменении любой из сторон: до этого разницы нет
+struct CopySite {
+    warning: Option<TranspilerError>,
+    // Имя, получившее копию
+    copy: String,
+}
+
+type SharedCopy = Rc<RefCell<CopySite>>;
+
+// Области видимости от внешней к внутренней
+type Scopes<'a> = Vec<Vec<Binding<'a>>>;
+
+// Состояние инициализации всех видимых переменных: (на всех путях, хотя бы на одном)
+type InitState = Vec<(bool, bool)>;
+
+// Анализ программы после разбора:
+// - отмечает переприсваиваемые переменные как mut;
+// - сообщает о чтении переменной, которой ещё не присвоено значение;
+// - предупреждает о переменных, значение которых нигде не читается
+pub fn analyze_program(lines: &mut [ParsedLine], diagnostics: &mut Diagnostics) {
+    let mut scopes = vec![Vec::new()];
+    analyze_block(lines, &mut scopes, diagnostics);
+    close_scope(&mut scopes, diagnostics);
+}
+
+// Поиск переменной, начиная с самой внутренней области видимости
+fn lookup<'s, 'a>(scopes: &'s mut Scopes<'a>, name: &str) -> Option<&'s mut Binding<'a>> {
+    scopes.iter_mut().rev().find_map(|scope| scope.iter_mut().rev().find(|binding| binding.name == name))
+}
+
+// Добавление объявления в текущую область видимости
+fn declare<'a>(scopes: &mut Scopes<'a>, name: &str, mutable: &'a mut bool, span: Span, initialized: bool) {
+    scopes.last_mut().unwrap().push(Binding {
+        name: name.to_string(),
+        mutable,
+        span,
+        initialized,
+        maybe_initialized: initialized,
+        used: false,
+        warn_unused: true,
+        copies: Vec::new(),
+    });
+}
+
+// Регистрация копии коллекции: copy получает значение source (переменной, её элемента
+// или аргумента вызова, если source - None)
+fn record_copy(scopes: &mut Scopes, copy: &str, source: Option<&Expression>, warning: TranspilerError) {
+    let site = Rc::new(RefCell::new(CopySite { warning: Some(warning), copy: copy.to_string() }));
+    if let Some(binding) = lookup(scopes, copy) {
+        binding.copies.push(Rc::clone(&site));
+    }
+    if let Some(binding) = source.and_then(|source| source.root_variable()).and_then(|name| lookup(scopes, name)) {
+        binding.copies.push(site);
+    }
+}
+
+// Копия коллекции при присваивании: значение - переменная или её элемент, а не новая коллекция
+fn copy_on_assignment(scopes: &mut Scopes, name: &str, value: &Expression) {
+    if value.root_variable().is_none() || !is_mutable_collection(value.get_type()) {
+        return;
+    }
+    let mut warning = TranspilerError::warning(
+        &format!("'{}' получает копию коллекции, а не ссылку на неё, как в Python", name),
+        value.span(),
+    )
+    .with_code("collection-copy")
+    .with_note("list, dict, set и bytearray копируются при присваивании и передаче в функцию");
+    if let (Expression::Variable { name: source, .. }, true) =
+        (value, list_element_type(value.get_type()).is_some() || value.get_type() == "bytearray")
+    {
+        warning = warning.with_help(&format!("если копия нужна намеренно, запишите её явно: {} = {}[:]", name, source));
+    }
+    record_copy(scopes, name, Some(value), warning);
+}
+
+// Закрытие области видимости с предупреждениями о неиспользуемых переменных
+fn close_scope(scopes: &mut Scopes, diagnostics: &mut Diagnostics) {
+    for binding in scopes.pop().unwrap_or_default() {
+        if binding.warn_unused && !binding.used && !binding.name.starts_with('_') {
+            diagnostics.report(
+                TranspilerError::warning(&format!("Переменная '{}' не используется", binding.name), binding.span)
+                    .with_code("unused-variable")
+                    .with_help(&format!("удалите переменную или начните её имя с _: _{}", binding.name)),
+            );
+        }
+    }
+}
+
+// Проверка, что переменной присвоено значение на всех путях до чтения
+fn check_initialized(scopes: &mut Scopes, name: &str, span: Span, diagnostics: &mut Diagnostics) {
+    if let Some(binding) = lookup(scopes, name) {
+        if !binding.initialized {
+            let reason = if binding.maybe_initialized {
+                "значение присваивается не на всех путях выполнения"
+            } else {
+                "объявлена без значения"
+            };
+            diagnostics.report(
+                TranspilerError::at(&format!("Переменная '{}' используется до присваивания значения", name), span)
+                    .with_code("uninitialized")
+                    .with_label(binding.span, reason)
+                    .with_help(&format!("присвойте значение при объявлении: {}: тип = значение", name)),
+            );
+            // Об одной переменной сообщаем один раз
+            binding.initialized = true;
+        }
+    }
+}
+
+// Чтение переменных в выражении
+fn read_expression(expr: &Expression, scopes: &mut Scopes, diagnostics: &mut Diagnostics) {
+    match expr {
+        Expression::Variable { name, span, .. } => {
+            check_initialized(scopes, name, *span, diagnostics);
+            if let Some(binding) = lookup(scopes, name) {
+                binding.used = true;
+            }
+        }
+        Expression::BinaryOp { left, right, .. } => {
+            read_expression(left, scopes, diagnostics);
+            read_expression(right, scopes, diagnostics);
+        }
+        Expression::UnaryOp { expr, .. } | Expression::Conversion { value: expr, .. } => {
+            read_expression(expr, scopes, diagnostics);
+        }
+        Expression::Call { args, .. }
+        | Expression::BuiltinCall { args, .. }
+        | Expression::List { elements: args, .. }
+        | Expression::Set { elements: args, .. }
+        | Expression::Tuple { elements: args, .. } => {
+            for arg in args {
+                read_expression(arg, scopes, diagnostics);
+            }
+        }
+        Expression::Index { target, index, .. } | Expression::Bound { value: target, body: index, .. } => {
+            read_expression(target, scopes, diagnostics);
+            read_expression(index, scopes, diagnostics);
+        }
+        Expression::Dict { entries, .. } => {
+            for (key, value) in entries {
+                read_expression(key, scopes, diagnostics);
+                read_expression(value, scopes, diagnostics);
+            }
+        }
+        Expression::Slice { target, start, stop, .. } => {
+            read_expression(target, scopes, diagnostics);
+            for bound in start.iter().chain(stop.iter()) {
+                read_expression(bound, scopes, diagnostics);
+            }
+        }
+        Expression::MethodCall { object, method, args, .. } => {
+            read_expression(object, scopes, diagnostics);
+            for arg in args {
+                read_expression(arg, scopes, diagnostics);
+            }
+            if is_mutating_method(method) {
+                mutate(scopes, object, expr.span(), diagnostics);
+            }
+        }
+        Expression::FormatString { parts, .. } => {
+            for part in parts {
+                if let FormatPart::Value { expr, .. } = part {
+                    read_expression(expr, scopes, diagnostics);
+                }
+            }
+        }
+        Expression::Literal { .. } => {}
+    }
+}
+
+// Присваивание: повторное присваивание требует mut. После него переменная
+// больше не связана с прежними копиями - в Python она тоже ссылалась бы на новое значение
+fn assign(scopes: &mut Scopes, name: &str) {
+    if let Some(binding) = lookup(scopes, name) {
+        if binding.maybe_initialized {
+            *binding.mutable = true;
+        }
+        binding.initialized = true;
+        binding.maybe_initialized = true;
+        for site in binding.copies.drain(..) {
+            site.borrow_mut().warning = None;
+        }
+    }
+}
+
+// Изменение коллекции на месте (a[i] = v, a.append(x), s.add(x), del d[k]) требует mut у переменной;
+// если коллекция была скопирована, изменение не видно другой стороне копии
+fn mutate(scopes: &mut Scopes, target: &Expression, span: Span, diagnostics: &mut Diagnostics) {
+    let Some(binding) = target.root_variable().and_then(|name| lookup(scopes, name)) else {
+        return;
+    };
+    *binding.mutable = true;
+    for site in binding.copies.drain(..) {
+        let mut site = site.borrow_mut();
+        if let Some(warning) = site.warning.take() {
+            let label = if site.copy == binding.name {
+                "изменение здесь не отразится на исходной коллекции".to_string()
+            } else {
+                format!("изменение здесь не отразится на '{}'", site.copy)
+            };
+            diagnostics.report(warning.with_label(span, &label));
+        }
+    }
+}
+
+// Текущее состояние инициализации всех видимых переменных
+fn init_state(scopes: &Scopes) -> InitState {
+    scopes
+        .iter()
+        .flatten()
+        .map(|binding| (binding.initialized, binding.maybe_initialized))
+        .collect()
+}
+
+// Восстановление состояния инициализации (набор видимых переменных тот же, что при сохранении)
+fn set_init_state(scopes: &mut Scopes, state: &InitState) {
+    for (binding, (initialized, maybe_initialized)) in scopes.iter_mut().flatten().zip(state) {
+        binding.initialized = *initialized;
+        binding.maybe_initialized = *maybe_initialized;
+    }
+}
+
+// Объединение состояний двух путей выполнения, сходящихся в одной точке
+fn merge_init_states(first: Option<InitState>, second: InitState) -> InitState {
+    match first {
+        None => second,
+        Some(first) => first
+            .into_iter()
+            .zip(second)
+            .map(|((a, maybe_a), (b, maybe_b))| (a && b, maybe_a || maybe_b))
+            .collect(),
+    }
+}
+
+// Может ли выполнение дойти до конца блока (а не выйти из него через return, break или continue)
+fn falls_through(lines: &[ParsedLine]) -> bool {
+    match lines.iter().rev().find(|line| !matches!(line, ParsedLine::Comment { .. } | ParsedLine::Empty)) {
+        Some(ParsedLine::Return { .. } | ParsedLine::Break { .. } | ParsedLine::Continue { .. }) => false,
+        Some(ParsedLine::If { branches, else_branch: Some(else_branch), .. }) => {
+            branches.iter().any(|b| falls_through(&b.body)) || falls_through(&else_branch.body)
+        }
+        _ => true,
+    }
+}
+
+// Имена, которым присваивается значение где-либо в блоке (включая вложенные блоки)
+fn assigned_names(lines: &[ParsedLine], names: &mut Vec<String>) {
+    for line in lines {
+        match line {
+            ParsedLine::VariableAssign { name, .. } | ParsedLine::CompoundAssign { name, .. } => names.push(name.clone()),
+            ParsedLine::TupleAssign { targets, declared, .. } => {
+                let assigned = targets.iter().zip(declared).filter(|(_, declared_type)| declared_type.is_none());
+                names.extend(assigned.map(|(name, _)| name.clone()));
+            }
+            ParsedLine::If { branches, else_branch, .. } => {
+                for branch in branches {
+                    assigned_names(&branch.body, names);
+                }
+                if let Some(block) = else_branch {
+                    assigned_names(&block.body, names);
+                }
+            }
+            ParsedLine::While { body, else_branch, .. } | ParsedLine::For { body, else_branch, .. } => {
+                assigned_names(body, names);
+                if let Some(block) = else_branch {
+                    assigned_names(&block.body, names);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// Анализ тела вложенного блока в отдельной области видимости
+fn analyze_nested<'a>(body: &'a mut [ParsedLine], scopes: &mut Scopes<'a>, diagnostics: &mut Diagnostics) {
+    scopes.push(Vec::new());
+    analyze_block(body, scopes, diagnostics);
+    close_scope(scopes, diagnostics);
+}
+
+// Анализ цикла: тело может не выполниться ни разу или выполниться многократно
+fn analyze_loop<'a>(
+    bindings: Vec<(&str, &'a mut bool, Span, Option<TranspilerError>)>,
+    body: &'a mut [ParsedLine],
+    else_branch: &'a mut Option<Block>,
+    scopes: &mut Scopes<'a>,
+    diagnostics: &mut Diagnostics,
+) {
+    // Присваивание внешней переменной в теле может выполниться повторно
+    let mut names = Vec::new();
+    assigned_names(body, &mut names);
+    for name in &names {
+        if let Some(binding) = lookup(scopes, name) {
+            binding.maybe_initialized = true;
+        }
+    }
+    let before = init_state(scopes);
+
+    scopes.push(Vec::new());
+    for (name, mutable, span, copy) in bindings {
+        declare(scopes, name, mutable, span, true);
+        if let Some(warning) = copy {
+            record_copy(scopes, name, None, warning);
+        }
+    }
+    analyze_block(body, scopes, diagnostics);
+    close_scope(scopes, diagnostics);
+
+    // Ветка else выполняется после завершения цикла, в том числе если тело не выполнялось
+    set_init_state(scopes, &before);
+    if let Some(block) = else_branch {
+        analyze_nested(&mut block.body, scopes, diagnostics);
+    }
+    set_init_state(scopes, &before);
+}
+
+// Анализ последовательности инструкций
+fn analyze_block<'a>(lines: &'a mut [ParsedLine], scopes: &mut Scopes<'a>, diagnostics: &mut Diagnostics) {
+    for line in lines.iter_mut() {
+        match line {
+            ParsedLine::Print { args, sep, end, .. } => {
+                for arg in args.iter().chain(sep.iter()).chain(end.iter()) {
+                    read_expression(arg, scopes, diagnostics);
+                }
+            }
+            ParsedLine::VariableDecl { name, value, mutable, span, .. } => {
+                if let Some(value) = value {
+                    read_expression(value, scopes, diagnostics);
+                }
+                // Объявление начинается с имени переменной
+                let name_span = Span::new(span.line, span.start, span.start + name.chars().count());
+                declare(scopes, name, mutable, name_span, value.is_some());
+                if let Some(value) = value {
+                    copy_on_assignment(scopes, name, value);
+                }
+            }
+            ParsedLine::VariableAssign { name, value, .. } => {
+                read_expression(value, scopes, diagnostics);
+                assign(scopes, name);
+                copy_on_assignment(scopes, name, value);
+            }
+            ParsedLine::TupleAssign { targets, target_spans, declared, mutable, value, .. } => {
+                read_expression(value, scopes, diagnostics);
+                for (((name, span), declared_type), mutable) in targets.iter().zip(target_spans).zip(declared.iter()).zip(mutable.iter_mut()) {
+                    match declared_type {
+                        Some(_) => declare(scopes, name, mutable, *span, true),
+                        None => assign(scopes, name),
+                    }
+                }
+            }
+            ParsedLine::CompoundAssign { name, value, span, .. } => {
+                // x += v читает текущее значение x, но не считается использованием переменной;
+                // для списка и bytearray, как в Python, это изменение на месте
+                read_expression(value, scopes, diagnostics);
+                check_initialized(scopes, name, *span, diagnostics);
+                if is_mutable_collection(value.get_type()) {
+                    let target = Expression::Variable { name: name.clone(), expr_type: value.get_type().to_string(), span: *span };
+                    mutate(scopes, &target, *span, diagnostics);
+                }
+                assign(scopes, name);
+            }
+            ParsedLine::ItemAssign { target, value, span, .. } => {
+                read_expression(value, scopes, diagnostics);
+                read_expression(target, scopes, diagnostics);
+                mutate(scopes, target, *span, diagnostics);
+            }
+            ParsedLine::Delete { target, span, .. } => {
+                read_expression(target, scopes, diagnostics);
+                mutate(scopes, target, *span, diagnostics);
+            }
+            ParsedLine::If { branches, else_branch, .. } => {
+                for branch in branches.iter() {
+                    read_expression(&branch.condition, scopes, diagnostics);
+                }
+                // Состояние после if объединяет ветки, из которых выполнение доходит до конца
+                let before = init_state(scopes);
+                let mut after = None;
+                for branch in branches.iter_mut() {
+                    set_init_state(scopes, &before);
+                    let reaches_end = falls_through(&branch.body);
+                    analyze_nested(&mut branch.body, scopes, diagnostics);
+                    if reaches_end {
+                        after = Some(merge_init_states(after, init_state(scopes)));
+                    }
+                }
+                match else_branch {
+                    Some(block) => {
+                        set_init_state(scopes, &before);
+                        let reaches_end = falls_through(&block.body);
+                        analyze_nested(&mut block.body, scopes, diagnostics);
+                        if reaches_end {
+                            after = Some(merge_init_states(after, init_state(scopes)));
+                        }
+                    }
+                    None => after = Some(merge_init_states(after, before.clone())),
+                }
+                // Если ни одна ветка не доходит до конца, код после if недостижим
+                let after = after.unwrap_or_else(|| before.iter().map(|(_, maybe)| (true, *maybe)).collect());
+                set_init_state(scopes, &after);
+            }
+            ParsedLine::While { condition, body, else_branch, .. } => {
+                read_expression(condition, scopes, diagnostics);
+                analyze_loop(Vec::new(), body, else_branch, scopes, diagnostics);
+            }
+            ParsedLine::For { var_names, var_spans, mutable, iterable, body, else_branch, .. } => {
+                match iterable {
+                    ForIterable::Range { start, stop, .. } => {
+                        read_expression(start, scopes, diagnostics);
+                        read_expression(stop, scopes, diagnostics);
+                    }
+                    ForIterable::Chars(expr)
+                    | ForIterable::Bytes(expr)
+                    | ForIterable::List(expr)
+                    | ForIterable::Keys(expr) => read_expression(expr, scopes, diagnostics),
+                }
+                // Цикл перебирает копии элементов: изменение вложенной коллекции не попадёт в список
+                let mut copied = match iterable {
+                    ForIterable::List(expr) if var_names.len() == 1 => list_element_type(expr.get_type())
+                        .filter(|element| is_mutable_collection(element))
+                        .map(|_| {
+                            TranspilerError::warning(
+                                &format!("Переменная цикла '{}' получает копию элемента, а не ссылку на него, как в Python", var_names[0]),
+                                expr.span(),
+                            )
+                            .with_code("collection-copy")
+                            .with_help("изменяйте элемент через индекс: for i in range(len(a)): a[i].append(x)")
+                        }),
+                    _ => None,
+                };
+                let bindings = var_names
+                    .iter()
+                    .zip(var_spans.iter())
+                    .zip(mutable.iter_mut())
+                    .map(|((name, span), mutable)| (name.as_str(), mutable, *span, copied.take()))
+                    .collect();
+                analyze_loop(bindings, body, else_branch, scopes, diagnostics);
+            }
+            ParsedLine::Function { name: function, params, param_spans, mutable_params, body, .. } => {
+                // Тело функции видит только свои параметры
+                let mut flags = vec![false; params.len()];
+                let mut function_scopes = vec![Vec::new()];
+                for (((name, param_type), span), mutable) in params.iter().zip(param_spans.iter()).zip(flags.iter_mut()) {
+                    declare(&mut function_scopes, name, mutable, *span, true);
+                    function_scopes[0].last_mut().unwrap().warn_unused = false;
+                    // Коллекция передаётся копией: изменения в функции не видны вызывающему коду
+                    if is_mutable_collection(param_type) {
+                        let warning = TranspilerError::warning(
+                            &format!("Изменение параметра '{}' не видно вызывающему коду", name),
+                            *span,
+                        )
+                        .with_code("collection-copy")
+                        .with_note("list, dict, set и bytearray копируются при передаче в функцию")
+                        .with_help(&format!(
+                            "верните изменённое значение и присвойте его: {} = {}({})",
+                            name,
+                            function,
+                            params.iter().map(|(param, _)| param.as_str()).collect::<Vec<_>>().join(", "),
+                        ));
+                        record_copy(&mut function_scopes, name, None, warning);
+                    }
+                }
+                analyze_block(body, &mut function_scopes, diagnostics);
+                close_scope(&mut function_scopes, diagnostics);
+                drop(function_scopes);
+                *mutable_params = params
+                    .iter()
+                    .zip(flags)
+                    .filter(|(_, mutable)| *mutable)
+                    .map(|((name, _), _)| name.clone())
+                    .collect();
+            }
+            ParsedLine::Return { value: Some(expr), .. } | ParsedLine::ExpressionStatement { expr, .. } => {
+                read_expression(expr, scopes, diagnostics);
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,565 @@
+use crate::error::{TranspilerError, Diagnostics};
+use crate::parser::{parse_line, parse_block_header, recover_def_header, line_indent, is_blank_or_comment, split_code_and_comment, strip_keyword, code_span};
+use crate::expressions::coerce_expression;
+use crate::types::{Span, ParsedLine, Expression, BlockHeader, ConditionalBranch, Block, function_type};
+use crate::symbols::{SymbolTable, SymbolKind};
+use std::collections::HashMap;
+
+// Контекст разбора блока: окружение, в котором находится текущая строка
+#[derive(Debug, Clone, Default)]
+struct BlockContext {
+    // Находимся ли внутри тела цикла
+    in_loop: bool,
+    // Метка ближайшего цикла (используется, если break/continue выходят из помеченного блока)
+    loop_label: Option<String>,
+    // Метки, которые должны указывать break и continue (None — без метки)
+    break_label: Option<String>,
+    continue_label: Option<String>,
+    // Тип результата и заголовок функции, внутри которой находится блок
+    return_type: Option<String>,
+    function_span: Option<Span>,
+}
+
+// Разбор всей программы с учётом блочной структуры по отступам
+pub fn parse_program(
+    lines: &[&str],
+    symbols: &mut SymbolTable,
+    diagnostics: &mut Diagnostics,
+) -> Vec<ParsedLine> {
+    // Функции и константы верхнего уровня можно использовать до их объявления
+    let mut declared: HashMap<String, Span> = HashMap::new();
+    for (i, line) in lines.iter().enumerate() {
+        let (code_part, _) = split_code_and_comment(line);
+        if line_indent(line) != 0 {
+            continue;
+        }
+        if strip_keyword(code_part.trim(), "const").is_some() {
+            // Ошибки в объявлении константы будут выведены при разборе строки
+            if let Ok(ParsedLine::Const { name, type_name, span, .. }) = parse_line(line, i + 1, symbols) {
+                if let Some(previous) = declared.get(&name) {
+                    diagnostics.report(
+                        TranspilerError::at(&format!("Имя '{}' уже объявлено", name), span).with_code("duplicate-definition")
+                            .with_label(*previous, "первое объявление здесь"),
+                    );
+                    continue;
+                }
+                declared.insert(name.clone(), span);
+                symbols.declare_item(&name, SymbolKind::Constant, &type_name, span);
+            }
+            continue;
+        }
+        if strip_keyword(code_part.trim(), "def").is_none() {
+            continue;
+        }
+        // Ошибки в заголовке будут выведены при разборе самой функции
+        let header = match parse_block_header(line, i + 1, symbols) {
+            Ok(header) => header.map(|(header, _)| header),
+            Err(_) => recover_def_header(line, i + 1),
+        };
+        if let Some(BlockHeader::Def { name, params, return_type }) = header {
+            let span = code_span(line, i + 1);
+            if let Some(previous) = declared.get(&name) {
+                diagnostics.report(
+                    TranspilerError::at(&format!("Имя '{}' уже объявлено", name), span).with_code("duplicate-definition")
+                        .with_label(*previous, "первое объявление здесь"),
+                );
+                continue;
+            }
+            declared.insert(name.clone(), span);
+            let param_types: Vec<String> = params.into_iter().map(|(_, t, _)| t).collect();
+            symbols.declare_item(&name, SymbolKind::Function, &function_type(&param_types, &return_type), span);
+        }
+    }
+
+    let mut pos = 0;
+    parse_block(lines, &mut pos, 0, &BlockContext::default(), symbols, diagnostics)
+}
+
+// Поиск следующей строки с кодом, начиная с позиции pos
+fn next_code_line(lines: &[&str], pos: usize) -> Option<usize> {
+    (pos..lines.len()).find(|&i| !is_blank_or_comment(lines[i]))
+}
+
+// Разбор последовательности строк с одинаковым отступом.
+// Ошибочные строки пропускаются (вместе с вложенными в них блоками),
+// чтобы сообщить обо всех ошибках файла за один запуск
+fn parse_block(
+    lines: &[&str],
+    pos: &mut usize,
+    indent: usize,
+    context: &BlockContext,
+    symbols: &mut SymbolTable,
+    diagnostics: &mut Diagnostics,
+) -> Vec<ParsedLine> {
+    let mut body = Vec::new();
+    // Инструкция (break, continue или return), после которой блок не выполняется
+    let mut terminator: Option<Span> = None;
+    let mut unreachable_reported = false;
+
+    while *pos < lines.len() {
+        let line = lines[*pos];
+        let line_num = *pos + 1;
+
+        // Пустые строки и комментарии относятся к блоку следующей строки кода
+        if is_blank_or_comment(line) {
+            if let Some(next) = next_code_line(lines, *pos) {
+                if line_indent(lines[next]) < indent {
+                    break;
+                }
+            }
+            parse_comments_until(lines, pos, line_num, &mut body, symbols, diagnostics);
+            continue;
+        }
+
+        let current_indent = line_indent(line);
+        if current_indent < indent {
+            break;
+        }
+        let span = code_span(line, line_num);
+        if current_indent > indent {
+            diagnostics.report(TranspilerError::new(
+                "Неожиданный отступ",
+                line_num,
+                span.start,
+            )
+            .with_code("indentation"));
+            *pos = skip_nested_block(lines, *pos, indent);
+            continue;
+        }
+
+        if let (Some(terminator), false) = (terminator, unreachable_reported) {
+            diagnostics.report(
+                TranspilerError::warning("Недостижимый код", span).with_code("unreachable-code")
+                    .with_label(terminator, "выполнение блока завершается здесь"),
+            );
+            unreachable_reported = true;
+        }
+
+        match parse_block_header(line, line_num, symbols) {
+            Ok(Some((BlockHeader::If { condition }, comment))) => {
+                *pos += 1;
+                body.push(parse_if_statement(lines, pos, condition, comment, context, symbols, diagnostics));
+            }
+            Ok(Some((header @ (BlockHeader::While { .. } | BlockHeader::For { .. }), comment))) => {
+                *pos += 1;
+                body.push(parse_loop_statement(lines, pos, header, comment, context, symbols, diagnostics));
+            }
+            Ok(Some((header @ BlockHeader::Def { .. }, comment))) => {
+                *pos += 1;
+                if indent != 0 || context.return_type.is_some() {
+                    diagnostics.report(TranspilerError::at(
+                        "Функции можно объявлять только на верхнем уровне",
+                        span,
+                    )
+                    .with_code("invalid-context"));
+                    *pos = skip_nested_block(lines, *pos, indent);
+                    continue;
+                }
+                body.push(parse_function(lines, pos, header, comment, symbols, diagnostics));
+            }
+            Ok(Some((header, _))) => {
+                let keyword = if matches!(header, BlockHeader::Else) { "else" } else { "elif" };
+                diagnostics.report(
+                    TranspilerError::at(&format!("'{}' без предшествующего 'if'", keyword), span).with_code("syntax")
+                        .with_help(&format!("'{}' должен иметь тот же отступ, что и соответствующий 'if'", keyword)),
+                );
+                *pos = skip_statement(lines, *pos, indent);
+            }
+            Err(e) => {
+                // Тело блока с ошибочным заголовком не разбирается
+                diagnostics.report(e);
+                *pos = skip_statement(lines, *pos, indent);
+            }
+            Ok(None) => {
+                let parsed = parse_line(line, line_num, symbols).and_then(|parsed| check_statement_context(parsed, indent, context));
+                match parsed {
+                    Ok(parsed) => {
+                        if matches!(parsed, ParsedLine::Break { .. } | ParsedLine::Continue { .. } | ParsedLine::Return { .. }) {
+                            terminator = terminator.or(parsed.span());
+                        }
+                        body.push(parsed);
+                    }
+                    Err(e) => diagnostics.report(e),
+                }
+                *pos += 1;
+            }
+        }
+    }
+
+    body
+}
+
+// Разбор тела составной инструкции (строки с отступом больше, чем у заголовка)
+fn parse_nested_block(
+    lines: &[&str],
+    pos: &mut usize,
+    header_indent: usize,
+    bindings: &[(String, String, Span)],
+    context: &BlockContext,
+    symbols: &mut SymbolTable,
+    diagnostics: &mut Diagnostics,
+) -> Vec<ParsedLine> {
+    let header_line = *pos;
+    let body_indent = match next_code_line(lines, *pos) {
+        Some(next) if line_indent(lines[next]) > header_indent => line_indent(lines[next]),
+        Some(next) => {
+            diagnostics.report(TranspilerError::new(
+                &format!("Ожидается блок с отступом после строки {}", header_line),
+                next + 1,
+                1,
+            )
+            .with_code("indentation"));
+            return Vec::new();
+        }
+        None => {
+            diagnostics.report(TranspilerError::new(
+                "Ожидается блок с отступом, но достигнут конец файла",
+                header_line,
+                1,
+            )
+            .with_code("indentation"));
+            return Vec::new();
+        }
+    };
+
+    // Переменные, объявленные внутри блока (включая переменную цикла и параметры), не видны после него
+    symbols.push_scope();
+    for (name, type_name, span) in bindings {
+        if let Some(outer) = symbols.lookup(name).filter(|symbol| symbol.kind == SymbolKind::Variable) {
+            diagnostics.report(
+                TranspilerError::at(&format!("Переменная цикла '{}' перекрывает переменную внешнего блока", name), *span)
+                    .with_code("duplicate-definition")
+                    .with_label(outer.span, "переменная объявлена здесь")
+                    .with_help("выберите для переменной цикла другое имя"),
+            );
+            continue;
+        }
+        if let Err(e) = symbols.declare_variable(name, type_name, *span) {
+            diagnostics.report(e);
+        }
+    }
+    let body = parse_block(lines, pos, body_indent, context, symbols, diagnostics);
+    symbols.pop_scope();
+
+    // После блока отступ должен вернуться к одному из внешних уровней
+    if let Some(next) = next_code_line(lines, *pos) {
+        let next_indent = line_indent(lines[next]);
+        if next_indent > header_indent && next_indent < body_indent {
+            diagnostics.report(TranspilerError::at(
+                "Отступ не соответствует ни одному из внешних уровней",
+                code_span(lines[next], next + 1),
+            )
+            .with_code("indentation"));
+            *pos = skip_nested_block(lines, *pos, header_indent);
+        }
+    }
+
+    body
+}
+
+// Разбор комментариев и пустых строк до строки end
+fn parse_comments_until(
+    lines: &[&str],
+    pos: &mut usize,
+    end: usize,
+    body: &mut Vec<ParsedLine>,
+    symbols: &mut SymbolTable,
+    diagnostics: &mut Diagnostics,
+) {
+    while *pos < end {
+        match parse_line(lines[*pos], *pos + 1, symbols) {
+            Ok(parsed) => body.push(parsed),
+            Err(e) => diagnostics.report(e),
+        }
+        *pos += 1;
+    }
+}
+
+// Проверка, что break и continue находятся внутри цикла, return — внутри функции,
+// а константы объявляются на верхнем уровне
+fn check_statement_context(
+    parsed: ParsedLine,
+    indent: usize,
+    context: &BlockContext,
+) -> Result<ParsedLine, TranspilerError> {
+    let span = parsed.span().unwrap_or_default();
+    match parsed {
+        ParsedLine::Break { .. } | ParsedLine::Continue { .. } if !context.in_loop => {
+            let keyword = if matches!(parsed, ParsedLine::Break { .. }) { "break" } else { "continue" };
+            Err(TranspilerError::at(&format!("'{}' вне цикла", keyword), span).with_code("invalid-context"))
+        }
+        ParsedLine::Break { comment, .. } => Ok(ParsedLine::Break {
+            label: context.break_label.clone(),
+            comment,
+            span,
+        }),
+        ParsedLine::Continue { comment, .. } => Ok(ParsedLine::Continue {
+            label: context.continue_label.clone(),
+            comment,
+            span,
+        }),
+        ParsedLine::Const { .. } if indent != 0 || context.return_type.is_some() => {
+            Err(TranspilerError::at("Константы можно объявлять только на верхнем уровне", span).with_code("invalid-context"))
+        }
+        ParsedLine::Return { value, comment, .. } => {
+            let return_type = match &context.return_type {
+                Some(return_type) => return_type,
+                None => return Err(TranspilerError::at("'return' вне функции", span).with_code("invalid-context")),
+            };
+            let value = value.map(|v| coerce_expression(v, return_type)).transpose()?;
+            let value_type = value.as_ref().map_or("None", |v| v.get_type());
+            if value_type != return_type {
+                let mut error = TranspilerError::at(
+                    &format!("Функция должна возвращать {}, а возвращается {}", return_type, value_type),
+                    value.as_ref().map_or(span, |v| v.span()),
+                )
+                .with_code("type-mismatch");
+                if let Some(function_span) = context.function_span {
+                    error = error.with_label(function_span, &format!("тип результата {} указан здесь", return_type));
+                }
+                return Err(error);
+            }
+            Ok(ParsedLine::Return { value, comment, span })
+        }
+        other => Ok(other),
+    }
+}
+
+// Проверка, используется ли метка хотя бы одним break или continue в блоке
+fn uses_label(lines: &[ParsedLine], label: &str) -> bool {
+    lines.iter().any(|line| match line {
+        ParsedLine::Break { label: Some(l), .. } | ParsedLine::Continue { label: Some(l), .. } => l == label,
+        ParsedLine::If { branches, else_branch, .. } => {
+            branches.iter().any(|b| uses_label(&b.body, label))
+                || else_branch.as_ref().is_some_and(|b| uses_label(&b.body, label))
+        }
+        ParsedLine::While { body, else_branch, .. } | ParsedLine::For { body, else_branch, .. } => {
+            uses_label(body, label)
+                || else_branch.as_ref().is_some_and(|b| uses_label(&b.body, label))
+        }
+        _ => false,
+    })
+}
+
+// Поиск продолжения составной инструкции (elif/else) на уровне заголовка
+fn next_continuation(lines: &[&str], pos: usize, indent: usize) -> Option<(usize, &'static str)> {
+    let next = next_code_line(lines, pos).filter(|&next| line_indent(lines[next]) == indent)?;
+    let (code_part, _) = split_code_and_comment(lines[next]);
+    ["elif", "else"]
+        .into_iter()
+        .find(|keyword| strip_keyword(code_part.trim(), keyword).is_some())
+        .map(|keyword| (next, keyword))
+}
+
+// Разбор цепочки if/elif/else
+fn parse_if_statement(
+    lines: &[&str],
+    pos: &mut usize,
+    condition: Expression,
+    comment: Option<String>,
+    context: &BlockContext,
+    symbols: &mut SymbolTable,
+    diagnostics: &mut Diagnostics,
+) -> ParsedLine {
+    // Заголовок находится на строке перед телом
+    let indent = line_indent(lines[*pos - 1]);
+    let span = code_span(lines[*pos - 1], *pos);
+    let body = parse_nested_block(lines, pos, indent, &[], context, symbols, diagnostics);
+    let mut branches = vec![ConditionalBranch { condition, body, comment }];
+    let mut else_branch = None;
+
+    while let Some((next, keyword)) = next_continuation(lines, *pos, indent) {
+        // Комментарии между ветками остаются в конце предыдущей ветки
+        let previous_body = &mut branches.last_mut().unwrap().body;
+        parse_comments_until(lines, pos, next, previous_body, symbols, diagnostics);
+        *pos = next + 1;
+
+        match parse_block_header(lines[next], next + 1, symbols) {
+            Ok(Some((BlockHeader::Elif { condition }, comment))) => {
+                let body = parse_nested_block(lines, pos, indent, &[], context, symbols, diagnostics);
+                branches.push(ConditionalBranch { condition, body, comment });
+            }
+            Ok(Some((_, comment))) => {
+                let body = parse_nested_block(lines, pos, indent, &[], context, symbols, diagnostics);
+                else_branch = Some(Block { body, comment });
+                break;
+            }
+            Ok(None) => unreachable!("строка начинается с elif или else"),
+            Err(e) => {
+                diagnostics.report(e);
+                *pos = skip_nested_block(lines, *pos, indent);
+                if keyword == "else" {
+                    break;
+                }
+            }
+        }
+    }
+
+    ParsedLine::If { branches, else_branch, span }
+}
+
+// Разбор цикла (while или for) с необязательной веткой else
+fn parse_loop_statement(
+    lines: &[&str],
+    pos: &mut usize,
+    header: BlockHeader,
+    comment: Option<String>,
+    context: &BlockContext,
+    symbols: &mut SymbolTable,
+    diagnostics: &mut Diagnostics,
+) -> ParsedLine {
+    let indent = line_indent(lines[*pos - 1]);
+    let span = code_span(lines[*pos - 1], *pos);
+    let (keyword, bindings) = match &header {
+        BlockHeader::For { vars, .. } => ("for", vars.clone()),
+        _ => ("while", Vec::new()),
+    };
+
+    // Наличие ветки else определяет, куда должен выходить break
+    let has_else = matches!(
+        next_continuation(lines, skip_nested_block(lines, *pos, indent), indent),
+        Some((_, "else"))
+    );
+    let loop_label = format!("'loop_{}", *pos);
+    let else_label = has_else.then(|| format!("'{}_{}", keyword, *pos));
+
+    let loop_context = BlockContext {
+        in_loop: true,
+        loop_label: Some(loop_label.clone()),
+        break_label: else_label.clone(),
+        continue_label: None,
+        return_type: context.return_type.clone(),
+        function_span: context.function_span,
+    };
+    let mut body = parse_nested_block(lines, pos, indent, &bindings, &loop_context, symbols, diagnostics);
+
+    let mut else_branch = None;
+    if let Some((next, "else")) = next_continuation(lines, *pos, indent) {
+        parse_comments_until(lines, pos, next, &mut body, symbols, diagnostics);
+        *pos = next + 1;
+
+        match parse_block_header(lines[next], next + 1, symbols) {
+            Ok(Some((_, else_comment))) => {
+                // Ветка else находится внутри помеченного блока, поэтому break и continue
+                // внешнего цикла должны явно указывать его метку
+                let else_context = BlockContext {
+                    break_label: context.break_label.clone().or_else(|| context.loop_label.clone()),
+                    continue_label: context.continue_label.clone().or_else(|| context.loop_label.clone()),
+                    ..context.clone()
+                };
+                let else_body = parse_nested_block(lines, pos, indent, &[], &else_context, symbols, diagnostics);
+                else_branch = Some(Block { body: else_body, comment: else_comment });
+            }
+            Ok(None) => unreachable!("строка начинается с else"),
+            Err(e) => {
+                diagnostics.report(e);
+                *pos = skip_nested_block(lines, *pos, indent);
+            }
+        }
+    }
+
+    // Метки выводятся, только если на них есть ссылки
+    let loop_label = Some(loop_label).filter(|l| uses_label(&body, l));
+    let else_label = else_label.filter(|l| uses_label(&body, l));
+
+    match header {
+        BlockHeader::For { vars, iterable } => ParsedLine::For {
+            mutable: vec![false; vars.len()],
+            var_spans: vars.iter().map(|(_, _, span)| *span).collect(),
+            var_names: vars.into_iter().map(|(name, _, _)| name).collect(),
+            iterable,
+            body,
+            else_branch,
+            loop_label,
+            else_label,
+            comment,
+            span,
+        },
+        BlockHeader::While { condition } => ParsedLine::While {
+            condition,
+            body,
+            else_branch,
+            loop_label,
+            else_label,
+            comment,
+            span,
+        },
+        _ => unreachable!("parse_loop_statement вызывается только для циклов"),
+    }
+}
+
+// Пропуск строк тела блока (с отступом больше, чем у заголовка)
+fn skip_nested_block(lines: &[&str], pos: usize, header_indent: usize) -> usize {
+    let mut end = pos;
+    while let Some(next) = next_code_line(lines, end) {
+        if line_indent(lines[next]) <= header_indent {
+            break;
+        }
+        end = next + 1;
+    }
+    end
+}
+
+// Пропуск составной инструкции целиком: заголовка, тела и веток elif/else
+fn skip_statement(lines: &[&str], pos: usize, indent: usize) -> usize {
+    let mut end = skip_nested_block(lines, pos + 1, indent);
+    while let Some((next, _)) = next_continuation(lines, end, indent) {
+        end = skip_nested_block(lines, next + 1, indent);
+    }
+    end
+}
+
+// Проверка, что блок завершается return на всех путях выполнения
+fn always_returns(lines: &[ParsedLine]) -> bool {
+    match lines.iter().rev().find(|line| !matches!(line, ParsedLine::Comment { .. } | ParsedLine::Empty)) {
+        Some(ParsedLine::Return { .. }) => true,
+        Some(ParsedLine::If { branches, else_branch: Some(else_branch), .. }) => {
+            branches.iter().all(|b| always_returns(&b.body)) && always_returns(&else_branch.body)
+        }
+        _ => false,
+    }
+}
+
+// Разбор объявления функции
+fn parse_function(
+    lines: &[&str],
+    pos: &mut usize,
+    header: BlockHeader,
+    comment: Option<String>,
+    symbols: &mut SymbolTable,
+    diagnostics: &mut Diagnostics,
+) -> ParsedLine {
+    let BlockHeader::Def { name, params, return_type } = header else {
+        unreachable!("parse_function вызывается только для def");
+    };
+    let span = code_span(lines[*pos - 1], *pos);
+
+    // Тело функции видит только функции, константы и свои параметры
+    let outer_scopes = symbols.enter_function();
+
+    let function_context = BlockContext {
+        return_type: Some(return_type.clone()),
+        function_span: Some(span),
+        ..BlockContext::default()
+    };
+    let errors_before = diagnostics.error_count();
+    let body = parse_nested_block(lines, pos, 0, &params, &function_context, symbols, diagnostics);
+    symbols.leave_function(outer_scopes);
+
+    // Если в теле есть ошибки, отсутствующий return может быть их следствием
+    let body_has_errors = diagnostics.error_count() > errors_before;
+    if return_type != "None" && !body_has_errors && !always_returns(&body) {
+        diagnostics.report(
+            TranspilerError::at(
+                &format!("Функция '{}' должна возвращать значение типа {} на всех путях выполнения", name, return_type),
+                span,
+            )
+            .with_code("missing-return")
+            .with_help("добавьте 'return' в конец функции или во все ветки if/elif/else"),
+        );
+    }
+
+    let param_spans = params.iter().map(|(_, _, span)| *span).collect();
+    let params = params.into_iter().map(|(name, param_type, _)| (name, param_type)).collect();
+    ParsedLine::Function { name, params, param_spans, mutable_params: Vec::new(), return_type, body, comment, span }
+}
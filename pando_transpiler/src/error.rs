@@ -1,4 +1,34 @@
 use std::fmt;
+use crate::types::Span;
+
+// Важность сообщения: ошибка прерывает трансляцию, предупреждение — нет
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+// Дополнительная пометка фрагмента кода (например, место объявления)
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+// Пояснение под фрагментом кода: примечание или подсказка по исправлению
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoteKind {
+    Note,
+    Help,
+}
+
+// Пояснение; подсказка может содержать автоматическое исправление (замену фрагмента)
+#[derive(Debug, Clone)]
+pub struct Note {
+    pub kind: NoteKind,
+    pub message: String,
+    pub fix: Option<(Span, String)>,
+}
 
 // Вспомогательная структура для ошибок трансляции
 #[derive(Debug)]
@@ -6,6 +36,12 @@ pub struct TranspilerError {
     pub message: String,
     pub line: usize,
     pub column: usize,
+    pub end_column: usize,
+    pub severity: Severity,
+    // Устойчивый идентификатор вида ошибки (например, "type-mismatch")
+    pub code: Option<&'static str>,
+    pub labels: Vec<Label>,
+    pub notes: Vec<Note>,
 }
 
 impl TranspilerError {
@@ -14,14 +50,265 @@ impl TranspilerError {
             message: message.to_string(),
             line,
             column,
+            end_column: column + 1,
+            severity: Severity::Error,
+            code: None,
+            labels: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    // Ошибка, указывающая на фрагмент исходного кода
+    pub fn at(message: &str, span: Span) -> Self {
+        Self {
+            end_column: span.end.max(span.start + 1),
+            ..Self::new(message, span.line, span.start)
+        }
+    }
+
+    // Предупреждение, указывающее на фрагмент исходного кода
+    pub fn warning(message: &str, span: Span) -> Self {
+        Self {
+            severity: Severity::Warning,
+            ..Self::at(message, span)
+        }
+    }
+
+    pub fn with_code(mut self, code: &'static str) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    pub fn with_label(mut self, span: Span, message: &str) -> Self {
+        self.labels.push(Label { span, message: message.to_string() });
+        self
+    }
+
+    pub fn with_note(mut self, note: &str) -> Self {
+        self.notes.push(Note { kind: NoteKind::Note, message: note.to_string(), fix: None });
+        self
+    }
+
+    pub fn with_help(mut self, help: &str) -> Self {
+        self.notes.push(Note { kind: NoteKind::Help, message: help.to_string(), fix: None });
+        self
+    }
+
+    // Подсказка с исправлением: заменить фрагмент span на replacement
+    pub fn with_fix(mut self, help: &str, span: Span, replacement: &str) -> Self {
+        self.notes.push(Note {
+            kind: NoteKind::Help,
+            message: help.to_string(),
+            fix: Some((span, replacement.to_string())),
+        });
+        self
+    }
+
+    // Вывод в стиле rustc: путь к файлу, строка исходного кода и подчёркивание фрагмента
+    pub fn render(&self, path: &str, source: &[&str], color: bool) -> String {
+        let (level, level_color) = match self.severity {
+            Severity::Error => ("ошибка", RED),
+            Severity::Warning => ("предупреждение", YELLOW),
+        };
+
+        // Основная пометка и дополнительные, упорядоченные по строкам
+        let mut marks = vec![(self.line, self.column, self.end_column, '^', level_color, "")];
+        for label in &self.labels {
+            let end = label.span.end.max(label.span.start + 1);
+            marks.push((label.span.line, label.span.start, end, '-', BLUE, label.message.as_str()));
+        }
+        marks.retain(|mark| mark.0 >= 1 && mark.0 <= source.len());
+        marks.sort_by_key(|mark| (mark.0, mark.1));
+
+        let width = marks.iter().map(|mark| mark.0).max().unwrap_or(self.line).to_string().len();
+        let pad = " ".repeat(width);
+        let gutter = paint(&format!("{} |", pad), BLUE, color);
+
+        let mut out = format!(
+            "{}{}\n{}{} {}:{}:{}\n",
+            paint(level, level_color, color),
+            paint(&format!(": {}", self.message), BOLD, color),
+            pad,
+            paint("-->", BLUE, color),
+            path,
+            self.line,
+            self.column,
+        );
+
+        if !marks.is_empty() {
+            out.push_str(&format!("{}\n", gutter));
+            let mut previous_line = None;
+            for (i, &(line, start, end, symbol, mark_color, message)) in marks.iter().enumerate() {
+                if previous_line != Some(line) {
+                    if previous_line.is_some_and(|previous| line > previous + 1) {
+                        out.push_str(&format!("{}\n", paint("...", BLUE, color)));
+                    }
+                    let number = paint(&format!("{:>width$} |", line, width = width), BLUE, color);
+                    out.push_str(&format!("{} {}\n", number, source[line - 1]));
+                    previous_line = Some(line);
+                }
+
+                // Отступ подчёркивания повторяет табуляции исходной строки
+                let indent: String = source[line - 1]
+                    .chars()
+                    .take(start.saturating_sub(1))
+                    .map(|c| if c == '\t' { '\t' } else { ' ' })
+                    .collect();
+                let underline = symbol.to_string().repeat(end - start);
+                let text = if message.is_empty() { underline } else { format!("{} {}", underline, message) };
+                out.push_str(&format!("{} {}{}\n", gutter, indent, paint(&text, mark_color, color)));
+
+                let is_last = i + 1 == marks.len();
+                if is_last && !self.notes.is_empty() {
+                    out.push_str(&format!("{}\n", gutter));
+                }
+            }
+        }
+
+        for note in &self.notes {
+            let title = match note.kind {
+                NoteKind::Note => "= примечание:",
+                NoteKind::Help => "= подсказка:",
+            };
+            out.push_str(&format!("{} {} {}\n", pad, paint(title, BOLD, color), note.message));
+        }
+
+        out
+    }
+
+    // Сообщение в формате JSON (одна строка) для редакторов и других инструментов.
+    // Строки и столбцы нумеруются с 1, конец фрагмента не включается
+    pub fn to_json(&self, path: &str) -> String {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        let code = self.code.map_or("null".to_string(), json_string);
+        let position = Span::new(self.line, self.column, self.end_column);
+
+        let related: Vec<String> = self.labels.iter()
+            .map(|label| format!(
+                "{{\"message\":{},{}}}",
+                json_string(&label.message),
+                json_location(path, label.span),
+            ))
+            .collect();
+
+        let notes: Vec<String> = self.notes.iter()
+            .filter(|note| note.fix.is_none())
+            .map(|note| format!(
+                "{{\"kind\":{},\"message\":{}}}",
+                json_string(if note.kind == NoteKind::Note { "note" } else { "help" }),
+                json_string(&note.message),
+            ))
+            .collect();
+
+        let fixes: Vec<String> = self.notes.iter()
+            .filter_map(|note| note.fix.as_ref().map(|fix| (note, fix)))
+            .map(|(note, (span, replacement))| format!(
+                "{{\"message\":{},{},\"replacement\":{}}}",
+                json_string(&note.message),
+                json_location(path, *span),
+                json_string(replacement),
+            ))
+            .collect();
+
+        format!(
+            "{{\"severity\":{},\"code\":{},\"message\":{},{},\"related\":[{}],\"notes\":[{}],\"fixes\":[{}]}}",
+            json_string(severity),
+            code,
+            json_string(&self.message),
+            json_location(path, position),
+            related.join(","),
+            notes.join(","),
+            fixes.join(","),
+        )
+    }
+}
+
+// Строка в кавычках с экранированием по правилам JSON
+fn json_string(text: &str) -> String {
+    let mut out = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
         }
     }
+    out.push('"');
+    out
+}
+
+// Поля file, start и end для фрагмента исходного кода
+fn json_location(path: &str, span: Span) -> String {
+    format!(
+        "\"file\":{},\"start\":{{\"line\":{},\"column\":{}}},\"end\":{{\"line\":{},\"column\":{}}}",
+        json_string(path),
+        span.line,
+        span.start,
+        span.line,
+        span.end,
+    )
+}
+
+// ANSI-коды цветов для вывода в терминал
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+
+fn paint(text: &str, code: &str, color: bool) -> String {
+    if color {
+        format!("{}{}\x1b[0m", code, text)
+    } else {
+        text.to_string()
+    }
 }
 
 impl fmt::Display for TranspilerError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Строка {}:{} - {}", self.line, self.column, self.message)
+        if self.end_column > self.column + 1 {
+            write!(f, "Строка {}:{}-{} - {}", self.line, self.column, self.end_column - 1, self.message)
+        } else {
+            write!(f, "Строка {}:{} - {}", self.line, self.column, self.message)
+        }
     }
 }
 
-impl std::error::Error for TranspilerError {}
\ No newline at end of file
+impl std::error::Error for TranspilerError {}
+
+// Ошибки и предупреждения, накопленные при трансляции файла
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    pub messages: Vec<TranspilerError>,
+}
+
+impl Diagnostics {
+    pub fn report(&mut self, message: TranspilerError) {
+        self.messages.push(message);
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.messages.iter().filter(|m| m.severity == Severity::Error).count()
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.messages.iter().filter(|m| m.severity == Severity::Warning).count()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.error_count() > 0
+    }
+
+    // Сообщения в порядке их положения в файле
+    pub fn sorted(&self) -> Vec<&TranspilerError> {
+        let mut messages: Vec<&TranspilerError> = self.messages.iter().collect();
+        messages.sort_by_key(|m| (m.line, m.column));
+        messages
+    }
+}
\ No newline at end of file
@@ -1,361 +1,2206 @@
 use crate::error::TranspilerError;
-use crate::types::{Expression, BinaryOperator, UnaryOperator, is_numeric_type, is_bitwise_type, is_integer_type};
-use std::collections::HashMap;
+use crate::lexer::{decode_escapes, tokenize, Token, TokenKind};
+use crate::types::{Span, Expression, FormatPart, BinaryOperator, UnaryOperator, is_numeric_type, is_bitwise_type, is_integer_type, is_ordered_type, integer_range, is_conversion_allowed, parse_function_type, list_element_type, list_type, set_element_type, set_type, tuple_element_types, tuple_type, dict_types, dict_type, is_hashable_type, is_text_type, is_bytes_type, constant_integer};
+use crate::symbols::SymbolTable;
 
 // Парсинг выражения
 pub fn parse_expression(
     expr: &str, 
-    variables: &HashMap<String, String>,
+    symbols: &SymbolTable,
     line_num: usize,
     column: usize
 ) -> Result<Expression, TranspilerError> {
     let trimmed = expr.trim();
+    let column = column + (expr.chars().count() - expr.trim_start().chars().count());
     
-    // Обработка составных операторов присваивания
-    if let Some((name, op, value)) = parse_compound_assignment(trimmed) {
-        if !variables.contains_key(&name) {
-            return Err(TranspilerError::new(
-                &format!("Переменная '{}' не объявлена", name),
-                line_num,
-                column,
-            ));
-        }
-        
-        let var_type = variables.get(&name).unwrap().clone();
-        let value_expr = parse_expression(&value, variables, line_num, column)?;
-        let value_type = value_expr.get_type().to_string();
-        
-        // Проверка типов
-        if var_type != value_type {
-            return Err(TranspilerError::new(
-                &format!("Несовместимые типы: {} и {}", var_type, value_type),
-                line_num,
-                column,
-            ));
-        }
-        
-        return Ok(Expression::CompoundAssign {
-            name,
-            op,
-            value: Box::new(value_expr),
-            expr_type: var_type,
-        });
+    let mut parser = ExpressionParser {
+        tokens: tokenize(trimmed, line_num, column)?,
+        pos: 0,
+        symbols,
+        line_num,
+        column,
+    };
+    
+    let mut result = parser.parse_or()?;
+    // Значения через запятую без скобок образуют кортеж: return a, b
+    if parser.peek() == &TokenKind::Comma {
+        result = parser.parse_tuple(result, None)?;
+    }
+    if parser.peek() != &TokenKind::Eof {
+        return Err(parser.error_at_current("Лишние символы после выражения").with_code("syntax"));
     }
     
-    // Парсинг бинарных операций
-    parse_binary_expression(trimmed, variables, line_num, column)
+    Ok(result)
 }
 
-// Парсинг бинарных операций
-fn parse_binary_expression(
-    expr: &str,
-    variables: &HashMap<String, String>,
+// Парсер выражений методом подъёма по приоритетам
+struct ExpressionParser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    symbols: &'a SymbolTable,
     line_num: usize,
     column: usize,
-) -> Result<Expression, TranspilerError> {
-    // Приоритет операций (чем выше число, тем выше приоритет)
-    const PRECEDENCE: &[(BinaryOperator, &str)] = &[
-        (BinaryOperator::BitwiseOr, "|"),
-        (BinaryOperator::BitwiseXor, "^"),
-        (BinaryOperator::BitwiseAnd, "&"),
-        (BinaryOperator::Add, "+"),
-        (BinaryOperator::Subtract, "-"),
-        (BinaryOperator::Multiply, "*"),
-        (BinaryOperator::Divide, "/"),
-        (BinaryOperator::FloorDivide, "//"),
-        (BinaryOperator::Modulo, "%"),
-    ];
-    
-    // Ищем оператор с наименьшим приоритетом (с учетом скобок)
-    let mut paren_count = 0;
-    let mut best_pos = None;
-    let mut best_op = None;
-    let mut best_prec = usize::MAX;
+}
+
+// Приоритет бинарных арифметических и битовых операторов (чем выше число, тем выше приоритет)
+fn binary_operator_precedence(kind: &TokenKind) -> Option<(BinaryOperator, u8)> {
+    match kind {
+        TokenKind::Pipe => Some((BinaryOperator::BitwiseOr, 1)),
+        TokenKind::Caret => Some((BinaryOperator::BitwiseXor, 2)),
+        TokenKind::Ampersand => Some((BinaryOperator::BitwiseAnd, 3)),
+        TokenKind::ShiftLeft => Some((BinaryOperator::ShiftLeft, 4)),
+        TokenKind::ShiftRight => Some((BinaryOperator::ShiftRight, 4)),
+        TokenKind::Plus => Some((BinaryOperator::Add, 5)),
+        TokenKind::Minus => Some((BinaryOperator::Subtract, 5)),
+        TokenKind::Star => Some((BinaryOperator::Multiply, 6)),
+        TokenKind::Slash => Some((BinaryOperator::Divide, 6)),
+        TokenKind::DoubleSlash => Some((BinaryOperator::FloorDivide, 6)),
+        TokenKind::Percent => Some((BinaryOperator::Modulo, 6)),
+        _ => None,
+    }
+}
+
+// Операторы сравнения
+fn comparison_operator(kind: &TokenKind) -> Option<BinaryOperator> {
+    match kind {
+        TokenKind::Equal => Some(BinaryOperator::Equal),
+        TokenKind::NotEqual => Some(BinaryOperator::NotEqual),
+        TokenKind::Less => Some(BinaryOperator::Less),
+        TokenKind::LessEqual => Some(BinaryOperator::LessEqual),
+        TokenKind::Greater => Some(BinaryOperator::Greater),
+        TokenKind::GreaterEqual => Some(BinaryOperator::GreaterEqual),
+        _ => None,
+    }
+}
+
+impl ExpressionParser<'_> {
+    fn peek(&self) -> &TokenKind {
+        &self.tokens[self.pos].kind
+    }
     
-    let chars: Vec<char> = expr.chars().collect();
-    for (i, &ch) in chars.iter().enumerate() {
-        match ch {
-            '(' => paren_count += 1,
-            ')' => paren_count -= 1,
-            _ => {}
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        if self.pos + 1 < self.tokens.len() {
+            self.pos += 1;
+        }
+        token
+    }
+    
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), TokenKind::Identifier(name) if name == keyword)
+    }
+    
+    // Положение токена в исходной строке
+    fn span_of(&self, token: &Token) -> Span {
+        Span::new(self.line_num, self.column + token.start, self.column + token.end)
+    }
+    
+    fn error_at(&self, message: &str, token: &Token) -> TranspilerError {
+        TranspilerError::at(message, self.span_of(token))
+    }
+    
+    // Ошибка на текущем токене; в конце выражения указываем место сразу после последнего токена
+    fn error_at_current(&self, message: &str) -> TranspilerError {
+        match (self.peek(), self.pos.checked_sub(1)) {
+            (TokenKind::Eof, Some(previous)) => {
+                TranspilerError::new(message, self.line_num, self.column + self.tokens[previous].end)
+            }
+            _ => self.error_at(message, &self.tokens[self.pos]),
+        }
+    }
+    
+    fn expect(&mut self, kind: TokenKind, description: &str) -> Result<Token, TranspilerError> {
+        if self.peek() == &kind {
+            Ok(self.advance())
+        } else {
+            Err(self.error_at_current(&format!("Ожидается {}", description)).with_code("syntax"))
+        }
+    }
+    
+    // or имеет наименьший приоритет
+    fn parse_or(&mut self) -> Result<Expression, TranspilerError> {
+        let mut left = self.parse_and()?;
+        while self.is_keyword("or") {
+            self.advance();
+            let right = self.parse_and()?;
+            left = self.make_logical(BinaryOperator::Or, left, right)?;
+        }
+        Ok(left)
+    }
+    
+    fn parse_and(&mut self) -> Result<Expression, TranspilerError> {
+        let mut left = self.parse_not()?;
+        while self.is_keyword("and") {
+            self.advance();
+            let right = self.parse_not()?;
+            left = self.make_logical(BinaryOperator::And, left, right)?;
+        }
+        Ok(left)
+    }
+    
+    // not связывает слабее сравнений: not a == b означает not (a == b)
+    fn parse_not(&mut self) -> Result<Expression, TranspilerError> {
+        if self.is_keyword("not") {
+            let op_token = self.advance();
+            let inner_expr = self.parse_not()?;
+            
+            if inner_expr.get_type() != "bool" {
+                return Err(TranspilerError::at(
+                    &format!("Оператор not допустим только для типа bool, получен {}", inner_expr.get_type()),
+                    inner_expr.span(),
+                )
+                .with_code("type-mismatch"));
+            }
+            
+            let span = self.span_of(&op_token).to(inner_expr.span());
+            return Ok(Expression::UnaryOp {
+                op: UnaryOperator::Not,
+                expr: Box::new(inner_expr),
+                expr_type: "bool".to_string(),
+                span,
+            });
         }
         
-        if paren_count == 0 {
-            for (prec, (op, op_str)) in PRECEDENCE.iter().enumerate() {
-                if check_operator_at_position(expr, i, op_str) {
-                    if prec < best_prec {
-                        best_pos = Some(i);
-                        best_op = Some(*op);
-                        best_prec = prec;
-                        break;
-                    }
+        self.parse_comparison()
+    }
+    
+    // Сравнения, включая цепочки вида 0 < x < 10
+    fn parse_comparison(&mut self) -> Result<Expression, TranspilerError> {
+        let first = self.parse_binary(1)?;
+        let mut operands = vec![first];
+        let mut comparisons = Vec::new();
+        
+        while let Some(op) = self.peek_comparison() {
+            let op_token = self.advance();
+            if op == BinaryOperator::NotIn {
+                self.advance();
+            }
+            comparisons.push((op, op_token));
+            operands.push(self.parse_binary(1)?);
+        }
+        
+        if comparisons.is_empty() {
+            return Ok(operands.pop().unwrap());
+        }
+        
+        // Средние операнды цепочки с побочными эффектами вычисляются один раз: значение
+        // связывается с временным именем, а перед ним - и первый операнд, чтобы сохранить
+        // порядок вычисления слева направо
+        let mut bindings: Vec<Option<(String, Expression)>> = vec![None; operands.len()];
+        let mut taken = Vec::new();
+        for i in 1..comparisons.len() {
+            if !is_side_effect_free(&operands[i]) {
+                bindings[i] = Some(self.bind_operand(&mut operands[i], &mut taken));
+                if i == 1 && !is_side_effect_free(&operands[0]) {
+                    bindings[0] = Some(self.bind_operand(&mut operands[0], &mut taken));
+                }
+            }
+        }
+        
+        // Цепочка a < b < c раскрывается в (a < b) and (b < c)
+        let mut chain = Vec::new();
+        for (i, (op, op_token)) in comparisons.iter().enumerate() {
+            let (left, right) = if matches!(op, BinaryOperator::In | BinaryOperator::NotIn) {
+                self.check_membership(operands[i].clone(), operands[i + 1].clone(), op_token)?
+            } else {
+                unify_operands(operands[i].clone(), operands[i + 1].clone())?
+            };
+            operands[i] = left;
+            operands[i + 1] = right;
+            
+            let left_type = operands[i].get_type();
+            let right_type = operands[i + 1].get_type();
+            
+            let is_membership = matches!(op, BinaryOperator::In | BinaryOperator::NotIn);
+            if left_type != right_type && !is_membership {
+                return Err(self.error_at(
+                    &format!("Несовместимые типы в сравнении: {} и {}", left_type, right_type),
+                    op_token,
+                )
+                .with_code("type-mismatch")
+                .with_label(operands[i].span(), &format!("имеет тип {}", left_type))
+                .with_label(operands[i + 1].span(), &format!("имеет тип {}", right_type)));
+            }
+            
+            if !is_membership && !is_operator_valid_for_type(*op, left_type) {
+                return Err(self.error_at(
+                    &format!("Сравнение {} недопустимо для типа {}", op.as_str(), left_type),
+                    op_token,
+                )
+                .with_code("unsupported-operation"));
+            }
+            
+            chain.push(Expression::BinaryOp {
+                left: Box::new(operands[i].clone()),
+                op: *op,
+                right: Box::new(operands[i + 1].clone()),
+                expr_type: "bool".to_string(),
+                span: operands[i].span().to(operands[i + 1].span()),
+            });
+        }
+        
+        // Сборка справа налево: следующий средний операнд вычисляется, только если
+        // предыдущие сравнения истинны
+        let mut result = chain.pop().unwrap();
+        for (i, comparison) in chain.into_iter().enumerate().rev() {
+            result = Expression::BinaryOp {
+                span: comparison.span().to(result.span()),
+                left: Box::new(comparison),
+                op: BinaryOperator::And,
+                right: Box::new(result),
+                expr_type: "bool".to_string(),
+            };
+            let bound_here = if i == 0 { vec![i + 1, 0] } else { vec![i + 1] };
+            for index in bound_here {
+                if let Some((name, value)) = bindings[index].take() {
+                    result = Expression::Bound {
+                        span: result.span(),
+                        name,
+                        value: Box::new(value),
+                        body: Box::new(result),
+                    };
                 }
             }
         }
+        
+        Ok(result)
+    }
+    
+    // Замена операнда временной переменной; имя не совпадает с видимыми символами
+    fn bind_operand(&self, operand: &mut Expression, taken: &mut Vec<String>) -> (String, Expression) {
+        let name = (1..)
+            .map(|n| if n == 1 { "operand".to_string() } else { format!("operand{}", n) })
+            .find(|name| self.symbols.lookup(name).is_none() && !taken.contains(name))
+            .unwrap();
+        taken.push(name.clone());
+        let variable = Expression::Variable {
+            name: name.clone(),
+            expr_type: operand.get_type().to_string(),
+            span: operand.span(),
+        };
+        (name, std::mem::replace(operand, variable))
+    }
+    
+    // Оператор сравнения в текущей позиции, включая in и not in
+    fn peek_comparison(&self) -> Option<BinaryOperator> {
+        if let Some(op) = comparison_operator(self.peek()) {
+            return Some(op);
+        }
+        if self.is_keyword("in") {
+            return Some(BinaryOperator::In);
+        }
+        let next_is_in = matches!(
+            self.tokens.get(self.pos + 1).map(|token| &token.kind),
+            Some(TokenKind::Identifier(name)) if name == "in"
+        );
+        (self.is_keyword("not") && next_is_in).then_some(BinaryOperator::NotIn)
+    }
+    
+    // Проверка вхождения x in a: значение приводится к типу элементов списка, множества
+    // или байтов (uint8) либо к типу ключей словаря
+    fn check_membership(
+        &self,
+        value: Expression,
+        container: Expression,
+        op_token: &Token,
+    ) -> Result<(Expression, Expression), TranspilerError> {
+        let container_type = container.get_type().to_string();
+        // В строке ищется подстрока или символ
+        if is_text_type(&container_type) {
+            if !is_text_type(value.get_type()) && value.get_type() != "char" {
+                return Err(self.error_at(
+                    &format!("Несовместимые типы в проверке вхождения: {} и {}", value.get_type(), container_type),
+                    op_token,
+                )
+                .with_code("type-mismatch")
+                .with_label(value.span(), &format!("имеет тип {}, ожидается строка или char", value.get_type())));
+            }
+            return Ok((value, container));
+        }
+        let dict_key = dict_types(&container_type).map(|(key_type, _)| key_type);
+        if dict_key.is_some() {
+            self.check_known_elements(&container)?;
+        }
+        let set_element = set_element_type(&container_type);
+        let byte_element = is_bytes_type(&container_type).then_some("uint8");
+        let Some(element_type) = dict_key.as_deref().or(list_element_type(&container_type)).or(set_element).or(byte_element) else {
+            return Err(TranspilerError::at(
+                &format!("Оператор in недопустим для типа {}", container_type),
+                container.span(),
+            )
+            .with_code("unsupported-operation")
+            .with_label(self.span_of(op_token), "проверка вхождения"));
+        };
+        
+        // Пустой список или множество без типа принимает тип значения
+        if element_type == "?" {
+            let container_type = match set_element {
+                Some(_) => set_type(value.get_type()),
+                None => list_type(value.get_type()),
+            };
+            let container = coerce_expression(container, &container_type)?;
+            return Ok((value, container));
+        }
+        let value = coerce_expression(value, element_type)?;
+        if value.get_type() != element_type {
+            return Err(self.error_at(
+                &format!("Несовместимые типы в проверке вхождения: {} и {}", value.get_type(), container_type),
+                op_token,
+            )
+            .with_code("type-mismatch")
+            .with_label(value.span(), &format!("имеет тип {}", value.get_type()))
+            .with_label(container.span(), &format!("{} имеют тип {}", if dict_key.is_some() { "ключи" } else { "элементы" }, element_type)));
+        }
+        Ok((value, container))
     }
     
-    if let (Some(pos), Some(op)) = (best_pos, best_op) {
-        let left = &expr[..pos];
-        let right = &expr[pos + op.len()..];
+    // Арифметические и битовые операции; все левоассоциативны
+    fn parse_binary(&mut self, min_precedence: u8) -> Result<Expression, TranspilerError> {
+        let mut left = self.parse_unary()?;
         
-        let left_expr = parse_binary_expression(left, variables, line_num, column)?;
-        let right_expr = parse_binary_expression(right, variables, line_num, column)?;
+        while let Some((op, precedence)) = binary_operator_precedence(self.peek()) {
+            if precedence < min_precedence {
+                break;
+            }
+            let op_token = self.advance();
+            let right = self.parse_binary(precedence + 1)?;
+            left = self.make_binary(op, left, right, &op_token)?;
+        }
+        
+        Ok(left)
+    }
+    
+    // Бинарная операция над числами с согласованием и проверкой типов операндов;
+    // сложение строк и повторение строки s * n дают новую строку string, сложение байтов - байты
+    fn make_binary(
+        &self,
+        op: BinaryOperator,
+        left: Expression,
+        right: Expression,
+        op_token: &Token,
+    ) -> Result<Expression, TranspilerError> {
+        let is_repetition = |text: &Expression, count: &Expression| {
+            is_text_type(text.get_type()) && is_integer_type(count.get_type())
+        };
+        if op == BinaryOperator::Multiply && (is_repetition(&left, &right) || is_repetition(&right, &left)) {
+            return Ok(Expression::BinaryOp {
+                span: left.span().to(right.span()),
+                left: Box::new(left),
+                op,
+                right: Box::new(right),
+                expr_type: "string".to_string(),
+            });
+        }
+        // Как в Python, сумма bytes и bytearray имеет тип левого слагаемого
+        if op == BinaryOperator::Add && is_bytes_type(left.get_type()) && is_bytes_type(right.get_type()) {
+            return Ok(Expression::BinaryOp {
+                span: left.span().to(right.span()),
+                expr_type: left.get_type().to_string(),
+                left: Box::new(left),
+                op,
+                right: Box::new(right),
+            });
+        }
+        let (left, right) = unify_operands(left, right)?;
         
         // Проверка совместимости типов
-        let left_type = left_expr.get_type().to_string();
-        let right_type = right_expr.get_type().to_string();
+        let left_type = left.get_type().to_string();
+        let right_type = right.get_type().to_string();
         
         if left_type != right_type {
-            return Err(TranspilerError::new(
+            return Err(self.error_at(
                 &format!("Несовместимые типы в операции: {} и {}", left_type, right_type),
-                line_num,
-                column + pos,
-            ));
+                op_token,
+            )
+            .with_code("type-mismatch")
+            .with_label(left.span(), &format!("имеет тип {}", left_type))
+            .with_label(right.span(), &format!("имеет тип {}", right_type)));
         }
         
+        let expr_type = if op == BinaryOperator::Add && is_text_type(&left_type) { "string".to_string() } else { left_type.clone() };
+        
         // Проверка допустимости операции для типа
-        if !is_operator_valid_for_type(op, &left_type) {
-            return Err(TranspilerError::new(
+        if !is_operator_valid_for_type(op, &expr_type) {
+            return Err(self.error_at(
                 &format!("Операция {:?} недопустима для типа {}", op, left_type),
-                line_num,
-                column + pos,
-            ));
+                op_token,
+            )
+            .with_code("unsupported-operation"));
         }
         
-        return Ok(Expression::BinaryOp {
-            left: Box::new(left_expr),
+        let binary = Expression::BinaryOp {
+            span: left.span().to(right.span()),
+            left: Box::new(left),
             op,
-            right: Box::new(right_expr),
-            expr_type: left_type,
-        });
+            right: Box::new(right),
+            expr_type,
+        };
+        if binary.is_constant() {
+            return widen_constant(binary);
+        }
+        Ok(binary)
     }
     
-    // Если операторов нет, парсим как унарную операцию или атомарное выражение
-    parse_unary_expression(expr, variables, line_num, column)
-}
-
-// Парсинг унарных операций
-fn parse_unary_expression(
-    expr: &str,
-    variables: &HashMap<String, String>,
-    line_num: usize,
-    column: usize,
-) -> Result<Expression, TranspilerError> {
-    let trimmed = expr.trim();
-    
-    // Унарный минус
-    if trimmed.starts_with('-') {
-        let inner = &trimmed[1..].trim();
-        let inner_expr = parse_unary_expression(inner, variables, line_num, column + 1)?;
+    // Унарные операции связывают сильнее бинарных: -a * b означает (-a) * b
+    fn parse_unary(&mut self) -> Result<Expression, TranspilerError> {
+        let op = match self.peek() {
+            TokenKind::Minus => UnaryOperator::Negate,
+            TokenKind::Tilde => UnaryOperator::BitwiseNot,
+            _ => return self.parse_power(),
+        };
+        
+        let op_token = self.advance();
+        let inner_expr = self.parse_unary()?;
         let expr_type = inner_expr.get_type().to_string();
         
-        if !is_numeric_type(&expr_type) {
-            return Err(TranspilerError::new(
+        if op == UnaryOperator::Negate && !is_numeric_type(&expr_type) {
+            return Err(self.error_at(
                 &format!("Унарный минус недопустим для типа {}", expr_type),
-                line_num,
-                column,
-            ));
+                &op_token,
+            )
+            .with_code("unsupported-operation"));
+        }
+        if op == UnaryOperator::BitwiseNot && !is_bitwise_type(&expr_type) {
+            return Err(self.error_at(
+                &format!("Битовая инверсия недопустима для типа {}", expr_type),
+                &op_token,
+            )
+            .with_code("unsupported-operation"));
         }
         
-        return Ok(Expression::UnaryOp {
-            op: UnaryOperator::Negate,
+        let unary = Expression::UnaryOp {
+            op,
+            span: self.span_of(&op_token).to(inner_expr.span()),
             expr: Box::new(inner_expr),
             expr_type,
-        });
+        };
+        if unary.is_constant() {
+            return widen_constant(unary);
+        }
+        Ok(unary)
     }
     
-    // Битовая инверсия
-    if trimmed.starts_with('~') {
-        let inner = &trimmed[1..].trim();
-        let inner_expr = parse_unary_expression(inner, variables, line_num, column + 1)?;
-        let expr_type = inner_expr.get_type().to_string();
+    // Возведение в степень правоассоциативно и связывает сильнее унарного минуса слева:
+    // -2 ** 2 означает -(2 ** 2), а 2 ** -1 допустимо
+    fn parse_power(&mut self) -> Result<Expression, TranspilerError> {
+        let base = self.parse_postfix()?;
+        if self.peek() != &TokenKind::DoubleStar {
+            return Ok(base);
+        }
         
-        if !is_bitwise_type(&expr_type) {
-            return Err(TranspilerError::new(
-                &format!("Битовая инверсия недопустима для типа {}", expr_type),
-                line_num,
-                column,
-            ));
+        let op_token = self.advance();
+        let exponent = self.parse_unary()?;
+        self.make_binary(BinaryOperator::Power, base, exponent, &op_token)
+    }
+    
+    // Обращения к элементам и методам после атомарного выражения: a[i], a[1:3], a.pop()
+    fn parse_postfix(&mut self) -> Result<Expression, TranspilerError> {
+        let mut expr = self.parse_primary()?;
+        loop {
+            expr = match self.peek() {
+                TokenKind::LeftBracket => self.parse_subscript(expr)?,
+                TokenKind::Dot => self.parse_method_call(expr)?,
+                _ => return Ok(expr),
+            };
+        }
+    }
+    
+    // Проверка, что тип элементов коллекции известен: у пустых [] и {} без объявленного типа его нет
+    fn check_known_elements(&self, expr: &Expression) -> Result<(), TranspilerError> {
+        if expr.get_type().contains('?') {
+            return Err(TranspilerError::at("Тип элементов пустой коллекции неизвестен", expr.span())
+                .with_code("type-mismatch")
+                .with_help("объявите переменную с типом, например: имя: list[int] = [] или имя: dict[str, int] = {}"));
+        }
+        Ok(())
+    }
+    
+    // Индекс или граница среза: целое число любого целого типа
+    fn parse_index(&mut self) -> Result<Expression, TranspilerError> {
+        let index = self.parse_or()?;
+        if !is_integer_type(index.get_type()) {
+            return Err(TranspilerError::at(
+                &format!("Индекс должен быть целым числом, получен {}", index.get_type()),
+                index.span(),
+            )
+            .with_code("type-mismatch"));
         }
+        Ok(index)
+    }
+    
+    // Индексирование a[i], срез a[start:stop] или обращение к словарю по ключу d[k];
+    // строка индексируется по символам: s[i] - символ char, а срез - новая строка string;
+    // байт bytes и bytearray имеет тип uint8, а срез - тот же тип, что и исходные байты
+    fn parse_subscript(&mut self, target: Expression) -> Result<Expression, TranspilerError> {
+        self.advance();
+        self.check_known_elements(&target)?;
+        if let Some((key_type, value_type)) = dict_types(target.get_type()) {
+            return self.parse_key_subscript(target, &key_type, value_type);
+        }
+        if let Some(element_types) = tuple_element_types(target.get_type()) {
+            return self.parse_tuple_subscript(target, element_types);
+        }
+        let text_element = is_text_type(target.get_type()).then_some("char");
+        let byte_element = is_bytes_type(target.get_type()).then_some("uint8");
+        let Some(element_type) = list_element_type(target.get_type())
+            .or(text_element)
+            .or(byte_element)
+            .map(str::to_string)
+        else {
+            return Err(TranspilerError::at(
+                &format!("Индексирование недопустимо для типа {}", target.get_type()),
+                target.span(),
+            )
+            .with_code("unsupported-operation"));
+        };
         
-        return Ok(Expression::UnaryOp {
-            op: UnaryOperator::BitwiseNot,
-            expr: Box::new(inner_expr),
-            expr_type,
+        let start = if self.peek() == &TokenKind::Colon { None } else { Some(self.parse_index()?) };
+        if self.peek() != &TokenKind::Colon {
+            let close_token = self.expect(TokenKind::RightBracket, "']'")?;
+            let Some(index) = start else {
+                return Err(self.error_at("Ожидается индекс", &close_token).with_code("syntax"));
+            };
+            return Ok(Expression::Index {
+                span: target.span().to(self.span_of(&close_token)),
+                target: Box::new(target),
+                index: Box::new(index),
+                expr_type: element_type,
+            });
+        }
+        
+        self.advance();
+        let stop = if matches!(self.peek(), TokenKind::RightBracket | TokenKind::Colon) {
+            None
+        } else {
+            Some(self.parse_index()?)
+        };
+        if self.peek() == &TokenKind::Colon {
+            return Err(self.error_at_current("Шаг среза не поддерживается").with_code("unsupported-operation"));
+        }
+        let close_token = self.expect(TokenKind::RightBracket, "']'")?;
+        Ok(Expression::Slice {
+            span: target.span().to(self.span_of(&close_token)),
+            expr_type: if text_element.is_some() { "string".to_string() } else { target.get_type().to_string() },
+            target: Box::new(target),
+            start: start.map(Box::new),
+            stop: stop.map(Box::new),
+        })
+    }
+    
+    // Элемент кортежа t[i]: индекс - целочисленная константа в границах кортежа,
+    // чтобы тип элемента был известен при трансляции; отрицательный индекс заменяется позицией
+    fn parse_tuple_subscript(
+        &mut self,
+        target: Expression,
+        element_types: Vec<String>,
+    ) -> Result<Expression, TranspilerError> {
+        let index = self.parse_or()?;
+        if self.peek() == &TokenKind::Colon {
+            return Err(self.error_at_current("Срез кортежа не поддерживается").with_code("unsupported-operation"));
+        }
+        let close_token = self.expect(TokenKind::RightBracket, "']'")?;
+        let Some(value) = constant_integer(&index) else {
+            return Err(TranspilerError::at("Индекс кортежа должен быть целочисленной константой", index.span())
+                .with_code("invalid-range")
+                .with_note("тип элемента кортежа определяется при трансляции"));
+        };
+        let length = element_types.len() as i128;
+        let position = if value < 0 { value + length } else { value };
+        if !(0..length).contains(&position) {
+            return Err(TranspilerError::at(
+                &format!("Индекс {} вне границ кортежа из {} элемент(ов)", value, length),
+                index.span(),
+            )
+            .with_code("invalid-range")
+            .with_label(target.span(), &format!("имеет тип {}", target.get_type())));
+        }
+        Ok(Expression::Index {
+            span: target.span().to(self.span_of(&close_token)),
+            target: Box::new(target),
+            index: Box::new(Expression::Literal {
+                value: position.to_string(),
+                expr_type: "int".to_string(),
+                span: index.span(),
+            }),
+            expr_type: element_types[position as usize].clone(),
+        })
+    }
+    
+    // Обращение к значению словаря по ключу: d[k]; срезы словаря недопустимы
+    fn parse_key_subscript(
+        &mut self,
+        target: Expression,
+        key_type: &str,
+        value_type: String,
+    ) -> Result<Expression, TranspilerError> {
+        let key = self.parse_key(key_type)?;
+        if self.peek() == &TokenKind::Colon {
+            return Err(self.error_at_current("Срез недопустим для словаря").with_code("unsupported-operation"));
+        }
+        let close_token = self.expect(TokenKind::RightBracket, "']'")?;
+        Ok(Expression::Index {
+            span: target.span().to(self.span_of(&close_token)),
+            target: Box::new(target),
+            index: Box::new(key),
+            expr_type: value_type,
+        })
+    }
+    
+    // Ключ словаря, приведённый к типу ключей
+    fn parse_key(&mut self, key_type: &str) -> Result<Expression, TranspilerError> {
+        let key = coerce_expression(self.parse_or()?, key_type)?;
+        if key.get_type() != key_type {
+            return Err(TranspilerError::at(
+                &format!("Несовместимый тип ключа: ожидается {}, получен {}", key_type, key.get_type()),
+                key.span(),
+            )
+            .with_code("type-mismatch"));
+        }
+        Ok(key)
+    }
+    
+    // Аргументы вызова в скобках; возвращает их вместе с закрывающей скобкой
+    fn parse_arguments(&mut self) -> Result<(Vec<Expression>, Token), TranspilerError> {
+        self.expect(TokenKind::LeftParen, "'('")?;
+        let mut args = Vec::new();
+        if self.peek() != &TokenKind::RightParen {
+            loop {
+                args.push(self.parse_or()?);
+                if self.peek() != &TokenKind::Comma {
+                    break;
+                }
+                self.advance();
+            }
+        }
+        let close_token = self.expect(TokenKind::RightParen, "')' после аргументов")?;
+        Ok((args, close_token))
+    }
+    
+    // Вызов метода списка (a.append(x), a.pop(), a.insert(i, x), a.remove(x)),
+    // словаря (d.get(k, default), d.keys(), d.values()), строки (s.upper(), s.split(","))
+    // или байтов (b.hex(), b.decode())
+    fn parse_method_call(&mut self, object: Expression) -> Result<Expression, TranspilerError> {
+        self.advance();
+        let name_token = self.advance();
+        let TokenKind::Identifier(method) = name_token.kind.clone() else {
+            return Err(self.error_at("Ожидается имя метода", &name_token).with_code("syntax"));
+        };
+        self.check_known_elements(&object)?;
+        let object_type = object.get_type().to_string();
+        let (signature, methods) = if let Some(element_type) = list_element_type(&object_type) {
+            (list_method_signature(element_type, &method), "у списка есть методы append, pop, insert и remove")
+        } else if let Some(element_type) = set_element_type(&object_type) {
+            (set_method_signature(element_type, &method), "у множества есть методы add и discard")
+        } else if let Some((key_type, value_type)) = dict_types(&object_type) {
+            (dict_method_signature(&key_type, &value_type, &method), "у словаря есть методы get, keys, values и items")
+        } else if is_text_type(&object_type) {
+            (
+                text_method_signature(&method),
+                "у строки есть методы upper, lower, strip, split, join, replace, startswith, endswith, find и encode",
+            )
+        } else if object_type == "bytearray" {
+            (bytes_method_signature(&method).or(list_method_signature("uint8", &method)), "у bytearray есть методы append, extend, pop, insert, remove, hex и decode")
+        } else if object_type == "bytes" {
+            (bytes_method_signature(&method).filter(|_| method != "extend"), "у bytes есть методы hex и decode")
+        } else {
+            return Err(TranspilerError::at(
+                &format!("Вызов методов недопустим для типа {}", object_type),
+                object.span(),
+            )
+            .with_code("unsupported-operation"));
+        };
+        let Some((param_types, required, return_type)) = signature else {
+            return Err(self.error_at(&format!("У типа {} нет метода '{}'", object_type, method), &name_token)
+                .with_code("undeclared-name")
+                .with_help(methods));
+        };
+        
+        let (args, close_token) = self.parse_arguments()?;
+        let span = object.span().to(self.span_of(&close_token));
+        if args.len() < required || args.len() > param_types.len() {
+            let expected = if required == param_types.len() {
+                required.to_string()
+            } else {
+                format!("от {} до {}", required, param_types.len())
+            };
+            let error = self.error_at(
+                &format!("Метод '{}' принимает {} аргумент(ов), передано {}", method, expected, args.len()),
+                &name_token,
+            )
+            .with_code("argument-count");
+            return Err(match method.as_str() {
+                "get" if dict_types(&object_type).is_some() => {
+                    error.with_help("укажите значение по умолчанию для отсутствующего ключа: d.get(k, default)")
+                }
+                _ => error,
+            });
+        }
+        
+        let args = self.check_arguments(&format!("метода '{}'", method), args, &param_types)?;
+        if matches!(method.as_str(), "encode" | "decode") {
+            check_encoding(&args)?;
+        }
+        Ok(Expression::MethodCall {
+            object: Box::new(object),
+            method,
+            args,
+            expr_type: return_type,
+            span,
+        })
+    }
+    
+    // Приведение аргументов к типам параметров и проверка их совпадения;
+    // параметр-индекс (index) принимает значение любого целого типа, параметр text - строку str
+    // или string, texts - список таких строк, buffer - байты bytes, bytearray или list[uint8];
+    // string передаётся в параметр str по ссылке
+    fn check_arguments(
+        &self,
+        callee: &str,
+        args: Vec<Expression>,
+        param_types: &[String],
+    ) -> Result<Vec<Expression>, TranspilerError> {
+        let args = args
+            .into_iter()
+            .zip(param_types)
+            .map(|(arg_expr, param_type)| match param_type.as_str() {
+                "index" => coerce_expression(arg_expr, "int"),
+                "text" => Ok(arg_expr),
+                "texts" => coerce_expression(arg_expr, &list_type("str")),
+                "buffer" if list_element_type(arg_expr.get_type()).is_some() => coerce_expression(arg_expr, &list_type("uint8")),
+                "buffer" => Ok(arg_expr),
+                "str" if arg_expr.get_type() == "string" => Ok(Expression::Conversion {
+                    span: arg_expr.span(),
+                    value: Box::new(arg_expr),
+                    expr_type: "str".to_string(),
+                }),
+                _ => coerce_expression(arg_expr, param_type),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        for (arg_expr, param_type) in args.iter().zip(param_types) {
+            let matches = match param_type.as_str() {
+                "index" => is_integer_type(arg_expr.get_type()),
+                "text" => is_text_type(arg_expr.get_type()),
+                "texts" => list_element_type(arg_expr.get_type()).is_some_and(is_text_type),
+                "buffer" => is_bytes_type(arg_expr.get_type()) || arg_expr.get_type() == list_type("uint8"),
+                _ => arg_expr.get_type() == param_type,
+            };
+            if !matches {
+                let expected = match param_type.as_str() {
+                    "index" => "целое число",
+                    "text" => "строка",
+                    "texts" => "список строк",
+                    "buffer" => "bytes, bytearray или list[uint8]",
+                    _ => param_type,
+                };
+                return Err(TranspilerError::at(
+                    &format!(
+                        "Несовместимый тип аргумента {}: ожидается {}, получен {}",
+                        callee,
+                        expected,
+                        arg_expr.get_type()
+                    ),
+                    arg_expr.span(),
+                )
+                .with_code("type-mismatch"));
+            }
+        }
+        Ok(args)
+    }
+    
+    // Встроенная функция len: длина списка, размер множества, число пар словаря
+    // или число символов строки либо байтов
+    fn parse_len(&mut self, name_token: &Token) -> Result<Expression, TranspilerError> {
+        let (mut args, close_token) = self.parse_arguments()?;
+        if args.len() != 1 {
+            return Err(self.error_at(
+                &format!("Функция 'len' принимает 1 аргумент(ов), передано {}", args.len()),
+                name_token,
+            )
+            .with_code("argument-count"));
+        }
+        let arg_type = args[0].get_type();
+        let is_sized = list_element_type(arg_type).is_some() || set_element_type(arg_type).is_some() || dict_types(arg_type).is_some();
+        if !is_sized && !is_text_type(arg_type) && !is_bytes_type(arg_type) {
+            return Err(TranspilerError::at(
+                &format!("Функция len недопустима для типа {}", args[0].get_type()),
+                args[0].span(),
+            )
+            .with_code("unsupported-operation"));
+        }
+        Ok(Expression::BuiltinCall {
+            name: "len".to_string(),
+            args: vec![args.remove(0)],
+            expr_type: "int".to_string(),
+            span: self.span_of(name_token).to(self.span_of(&close_token)),
+        })
+    }
+    
+    // Встроенная функция set: set() - пустое множество, set(a) - множество элементов списка или множества
+    fn parse_set_call(&mut self, name_token: &Token) -> Result<Expression, TranspilerError> {
+        let (mut args, close_token) = self.parse_arguments()?;
+        let span = self.span_of(name_token).to(self.span_of(&close_token));
+        if args.len() > 1 {
+            return Err(self.error_at(
+                &format!("Функция 'set' принимает от 0 до 1 аргумент(ов), передано {}", args.len()),
+                name_token,
+            )
+            .with_code("argument-count"));
+        }
+        let Some(source) = args.pop() else {
+            return Ok(Expression::Set { elements: Vec::new(), expr_type: set_type("?"), span });
+        };
+        let source_type = source.get_type().to_string();
+        let Some(element_type) = list_element_type(&source_type).or(set_element_type(&source_type)) else {
+            return Err(TranspilerError::at(&format!("Нельзя построить множество из типа {}", source_type), source.span())
+                .with_code("type-mismatch"));
+        };
+        if element_type == "?" {
+            return Ok(Expression::Set { elements: Vec::new(), expr_type: set_type("?"), span });
+        }
+        check_set_element(element_type, source.span())?;
+        Ok(Expression::BuiltinCall {
+            name: "set".to_string(),
+            expr_type: set_type(element_type),
+            args: vec![source],
+            span,
+        })
+    }
+    
+    // Встроенные функции bytes и bytearray: bytearray() - пустые байты, bytearray(n) - n нулевых байтов,
+    // bytearray(b) - копия байтов, bytearray(a) - байты из списка целых чисел от 0 до 255
+    fn parse_bytes_call(&mut self, name: String, name_token: &Token) -> Result<Expression, TranspilerError> {
+        let (mut args, close_token) = self.parse_arguments()?;
+        let span = self.span_of(name_token).to(self.span_of(&close_token));
+        if args.len() > 1 {
+            return Err(self.error_at(
+                &format!("Функция '{}' принимает от 0 до 1 аргумент(ов), передано {}", name, args.len()),
+                name_token,
+            )
+            .with_code("argument-count"));
+        }
+        if let Some(source) = args.pop() {
+            let source = match list_element_type(source.get_type()) {
+                Some(_) => coerce_expression(source, &list_type("uint8"))?,
+                None => source,
+            };
+            let source_type = source.get_type();
+            let is_integer_list = list_element_type(source_type).is_some_and(is_integer_type);
+            if !is_integer_type(source_type) && !is_bytes_type(source_type) && !is_integer_list {
+                let error = TranspilerError::at(&format!("Нельзя построить {} из типа {}", name, source_type), source.span())
+                    .with_code("type-mismatch");
+                return Err(match is_text_type(source_type) {
+                    true => error.with_help("строка переводится в байты методом encode: s.encode()"),
+                    false => error,
+                });
+            }
+            args.push(source);
+        }
+        Ok(Expression::BuiltinCall { expr_type: name.clone(), name, args, span })
+    }
+    
+    // Разбор байтов из шестнадцатеричной записи: bytes.fromhex("de ad"), bytearray.fromhex(s)
+    fn parse_fromhex(&mut self, type_name: String, type_token: &Token) -> Result<Expression, TranspilerError> {
+        self.advance();
+        let name_token = self.advance();
+        if name_token.kind != TokenKind::Identifier("fromhex".to_string()) {
+            return Err(self.error_at(&format!("Ожидается {}.fromhex(строка)", type_name), &name_token)
+                .with_code("syntax")
+                .with_label(self.span_of(type_token), &format!("у типа {} есть только метод fromhex", type_name)));
+        }
+        let (args, close_token) = self.parse_arguments()?;
+        if args.len() != 1 {
+            return Err(self.error_at(
+                &format!("Метод 'fromhex' принимает 1 аргумент(ов), передано {}", args.len()),
+                &name_token,
+            )
+            .with_code("argument-count"));
+        }
+        let args = self.check_arguments("метода 'fromhex'", args, &["text".to_string()])?;
+        Ok(Expression::BuiltinCall {
+            name: "fromhex".to_string(),
+            args,
+            expr_type: type_name,
+            span: self.span_of(type_token).to(self.span_of(&close_token)),
+        })
+    }
+    
+    // Кортеж после первого элемента: (1, "a"), (x,) или 1, "a" без скобок (open_token - None);
+    // допускается запятая после последнего элемента
+    fn parse_tuple(&mut self, first: Expression, open_token: Option<&Token>) -> Result<Expression, TranspilerError> {
+        let mut elements = vec![first];
+        while self.peek() == &TokenKind::Comma {
+            self.advance();
+            if matches!(self.peek(), TokenKind::RightParen | TokenKind::Eof) {
+                break;
+            }
+            elements.push(self.parse_or()?);
+        }
+        let mut span = elements[0].span().to(elements[elements.len() - 1].span());
+        if let Some(open_token) = open_token {
+            let close_token = self.expect(TokenKind::RightParen, "')' в конце кортежа")?;
+            span = self.span_of(open_token).to(self.span_of(&close_token));
+        }
+        
+        if let Some(element) = elements.iter().find(|e| e.get_type() == "None" || parse_function_type(e.get_type()).is_some()) {
+            return Err(TranspilerError::at(
+                &format!("Значение типа {} не может храниться в кортеже", element.get_type()),
+                element.span(),
+            )
+            .with_code("type-mismatch"));
+        }
+        let element_types: Vec<String> = elements.iter().map(|e| e.get_type().to_string()).collect();
+        Ok(Expression::Tuple { elements, expr_type: tuple_type(&element_types), span })
+    }
+    
+    // Литерал списка: [1, 2, 3]; допускается запятая после последнего элемента
+    fn parse_list(&mut self, open_token: &Token) -> Result<Expression, TranspilerError> {
+        let mut elements = Vec::new();
+        while self.peek() != &TokenKind::RightBracket {
+            elements.push(self.parse_or()?);
+            if self.peek() != &TokenKind::Comma {
+                break;
+            }
+            self.advance();
+        }
+        let close_token = self.expect(TokenKind::RightBracket, "']' в конце списка")?;
+        let span = self.span_of(open_token).to(self.span_of(&close_token));
+        
+        if elements.is_empty() {
+            return Ok(Expression::List { elements, expr_type: list_type("?"), span });
+        }
+        
+        let element_type = common_type(&elements, "Элементы списка")?;
+        let elements = elements
+            .into_iter()
+            .map(|e| coerce_expression(e, &element_type))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Expression::List { elements, expr_type: list_type(&element_type), span })
+    }
+    
+    // Литерал в фигурных скобках: словарь {"a": 1}, если после первого элемента идёт ':',
+    // иначе множество {1, 2}; пустые скобки {}, как в Python, - пустой словарь
+    fn parse_braced(&mut self, open_token: &Token) -> Result<Expression, TranspilerError> {
+        if self.peek() == &TokenKind::RightBrace {
+            let close_token = self.advance();
+            let span = self.span_of(open_token).to(self.span_of(&close_token));
+            return Ok(Expression::Dict { entries: Vec::new(), expr_type: dict_type("?", "?"), span });
+        }
+        let first = self.parse_or()?;
+        if self.peek() == &TokenKind::Colon {
+            self.parse_dict(open_token, first)
+        } else {
+            self.parse_set(open_token, first)
+        }
+    }
+    
+    // Литерал множества {1, 2, 3} после первого элемента; повторы, как в Python, отбрасываются
+    fn parse_set(&mut self, open_token: &Token, first: Expression) -> Result<Expression, TranspilerError> {
+        let mut elements = vec![first];
+        while self.peek() == &TokenKind::Comma {
+            self.advance();
+            if self.peek() == &TokenKind::RightBrace {
+                break;
+            }
+            elements.push(self.parse_or()?);
+        }
+        let close_token = self.expect(TokenKind::RightBrace, "'}' в конце множества")?;
+        let span = self.span_of(open_token).to(self.span_of(&close_token));
+        
+        let element_type = common_type(&elements, "Элементы множества")?;
+        check_set_element(&element_type, elements[0].span())?;
+        let elements = elements
+            .into_iter()
+            .map(|e| coerce_expression(e, &element_type))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Expression::Set { elements, expr_type: set_type(&element_type), span })
+    }
+    
+    // Литерал словаря {"a": 1, "b": 2} после первого ключа; допускается запятая после последней пары
+    fn parse_dict(&mut self, open_token: &Token, first_key: Expression) -> Result<Expression, TranspilerError> {
+        let mut keys = vec![first_key];
+        let mut values = Vec::new();
+        loop {
+            self.expect(TokenKind::Colon, "':' после ключа")?;
+            values.push(self.parse_or()?);
+            if self.peek() != &TokenKind::Comma {
+                break;
+            }
+            self.advance();
+            if self.peek() == &TokenKind::RightBrace {
+                break;
+            }
+            keys.push(self.parse_or()?);
+        }
+        let close_token = self.expect(TokenKind::RightBrace, "'}' в конце словаря")?;
+        let span = self.span_of(open_token).to(self.span_of(&close_token));
+        
+        let key_type = common_type(&keys, "Ключи словаря")?;
+        if !is_hashable_type(&key_type) {
+            return Err(TranspilerError::at(&format!("Тип {} не может быть ключом словаря", key_type), keys[0].span())
+                .with_code("type-mismatch")
+                .with_note("ключом может быть целое число, bool, char, строка или bytes"));
+        }
+        let value_type = common_type(&values, "Значения словаря")?;
+        let entries = keys
+            .into_iter()
+            .zip(values)
+            .map(|(key, value)| Ok((coerce_expression(key, &key_type)?, coerce_expression(value, &value_type)?)))
+            .collect::<Result<Vec<_>, TranspilerError>>()?;
+        Ok(Expression::Dict { entries, expr_type: dict_type(&key_type, &value_type), span })
+    }
+    
+    // Атомарное выражение: литерал, переменная, вызов функции или выражение в скобках
+    fn parse_primary(&mut self) -> Result<Expression, TranspilerError> {
+        let token = self.advance();
+        let span = self.span_of(&token);
+        let literal = |value: String, expr_type: &str| Ok(Expression::Literal {
+            value,
+            expr_type: expr_type.to_string(),
+            span,
         });
+        
+        match token.kind.clone() {
+            TokenKind::Integer(value) => {
+                let value = value.replace('_', "");
+                match integer_literal_type(&value) {
+                    Some(expr_type) => literal(value, expr_type),
+                    None => Err(self.error_at("Слишком большое целое число", &token).with_code("invalid-range")),
+                }
+            }
+            // Значение, не помещающееся в float, получает тип double
+            TokenKind::Float(value) => {
+                let value = value.replace('_', "");
+                let number: f64 = value.parse().unwrap_or(f64::INFINITY);
+                if !number.is_finite() {
+                    return Err(range_error(&value, "double", span));
+                }
+                literal(value, if number > f32::MAX as f64 { "double" } else { "float" })
+            }
+            TokenKind::Str(content) => {
+                let escaped = crate::types::escape_string_for_rust(&content);
+                literal(format!("\"{}\"", escaped), "str")
+            }
+            TokenKind::Bytes(content) => {
+                let escaped = crate::types::escape_bytes_for_rust(&content);
+                literal(format!("b\"{}\"", escaped), "bytes")
+            }
+            TokenKind::FormatString(raw) => self.parse_format_string(&raw, &token),
+            TokenKind::Char(content) => {
+                if content.is_empty() {
+                    return Err(self.error_at("Пустой символьный литерал", &token).with_code("syntax"));
+                }
+                if content.chars().count() > 1 {
+                    return Err(self.error_at("Символьный литерал должен содержать один символ", &token).with_code("syntax"));
+                }
+                let escaped = match content.as_str() {
+                    "'" => "\\'".to_string(),
+                    _ => crate::types::escape_string_for_rust(&content),
+                };
+                literal(format!("'{}'", escaped), "char")
+            }
+            TokenKind::LeftParen => {
+                let inner = self.parse_or()?;
+                if self.peek() == &TokenKind::Comma {
+                    return self.parse_tuple(inner, Some(&token));
+                }
+                self.expect(TokenKind::RightParen, "')'")?;
+                Ok(inner)
+            }
+            TokenKind::LeftBracket => self.parse_list(&token),
+            TokenKind::LeftBrace => self.parse_braced(&token),
+            TokenKind::Identifier(name) => match name.as_str() {
+                "True" => literal("true".to_string(), "bool"),
+                "False" => literal("false".to_string(), "bool"),
+                "None" => literal("()".to_string(), "None"),
+                _ if is_numeric_type(&name) && self.peek() == &TokenKind::LeftParen => {
+                    self.parse_conversion(name, &token)
+                }
+                _ if self.peek() == &TokenKind::LeftParen => self.parse_call(name, &token),
+                _ if is_bytes_type(&name) && self.peek() == &TokenKind::Dot => self.parse_fromhex(name, &token),
+                "_" => Err(self.error_at("Имя '_' нельзя использовать как значение", &token).with_code("syntax")),
+                _ => match self.symbols.get_type(&name) {
+                    Some(expr_type) => Ok(Expression::Variable {
+                        name,
+                        expr_type: expr_type.to_string(),
+                        span,
+                    }),
+                    None => Err(self.error_at(&format!("Переменная '{}' не объявлена", name), &token).with_code("undeclared-name")),
+                },
+            },
+            TokenKind::Eof => Err(self.error_at("Ожидается выражение", &token).with_code("syntax")),
+            _ => Err(self.error_at("Некорректное выражение: ожидается значение", &token).with_code("syntax")),
+        }
+    }
+    
+    // Явное преобразование в числовой тип: int64(x), double(n), int(s)
+    fn parse_conversion(&mut self, target: String, name_token: &Token) -> Result<Expression, TranspilerError> {
+        self.expect(TokenKind::LeftParen, "'('")?;
+        let value = self.parse_or()?;
+        let close_token = self.expect(TokenKind::RightParen, "')': преобразование принимает один аргумент")?;
+        let span = self.span_of(name_token).to(self.span_of(&close_token));
+        
+        // Константа переводится в целевой тип сразу, с проверкой диапазона
+        let value = coerce_expression(value, &target)?;
+        if value.get_type() == target {
+            return Ok(value);
+        }
+        
+        if !is_conversion_allowed(value.get_type(), &target) {
+            return Err(TranspilerError::at(
+                &format!("Нельзя преобразовать {} в {}", value.get_type(), target),
+                value.span(),
+            )
+            .with_code("type-mismatch")
+            .with_label(self.span_of(name_token), &format!("преобразование в {}", target)));
+        }
+        
+        Ok(Expression::Conversion {
+            value: Box::new(value),
+            expr_type: target,
+            span,
+        })
+    }
+    
+    // f-строка: текст с подстановками {выражение[!s][:спецификатор]}, {{ и }} дают скобки
+    fn parse_format_string(&self, raw: &str, token: &Token) -> Result<Expression, TranspilerError> {
+        let chars: Vec<char> = raw.chars().collect();
+        // Колонка первого символа содержимого (после f")
+        let base = self.column + token.start + 2;
+        let error_at_char = |message: &str, i: usize| {
+            TranspilerError::at(message, Span::new(self.line_num, base + i, base + i + 1)).with_code("syntax")
+        };
+        
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut i = 0;
+        while i < chars.len() {
+            match chars[i] {
+                '{' if chars.get(i + 1) == Some(&'{') => {
+                    text.push('{');
+                    i += 2;
+                }
+                '}' if chars.get(i + 1) == Some(&'}') => {
+                    text.push('}');
+                    i += 2;
+                }
+                '}' => {
+                    return Err(error_at_char("Одиночная '}' в f-строке", i).with_help("для вывода скобки используйте '}}'"));
+                }
+                '{' => {
+                    let close = match find_field_end(&chars, i + 1) {
+                        Some(close) => close,
+                        None => return Err(error_at_char("Незакрытая '{' в f-строке", i)),
+                    };
+                    if !text.is_empty() {
+                        parts.push(FormatPart::Text(decode_escapes(&text)));
+                        text.clear();
+                    }
+                    parts.push(self.parse_format_field(&chars, i + 1, close, base)?);
+                    i = close + 1;
+                }
+                '\\' => {
+                    text.extend(chars.get(i..i + 2).unwrap_or(&chars[i..]));
+                    i += 2;
+                }
+                c => {
+                    text.push(c);
+                    i += 1;
+                }
+            }
+        }
+        if !text.is_empty() {
+            parts.push(FormatPart::Text(decode_escapes(&text)));
+        }
+        
+        Ok(Expression::FormatString {
+            parts,
+            span: self.span_of(token),
+        })
+    }
+    
+    // Подстановка f-строки между позициями start и end (без фигурных скобок)
+    fn parse_format_field(
+        &self,
+        chars: &[char],
+        start: usize,
+        end: usize,
+        base: usize,
+    ) -> Result<FormatPart, TranspilerError> {
+        let char_span = |from: usize, to: usize| Span::new(self.line_num, base + from, base + to);
+        
+        // Выражение заканчивается на ! (но не !=) или : верхнего уровня
+        let expr_end = top_level_field_end(chars, start, end);
+        let expr_text: String = chars[start..expr_end].iter().collect();
+        if expr_text.trim().is_empty() {
+            return Err(TranspilerError::at("Пустое выражение в f-строке", char_span(start - 1, end + 1)).with_code("syntax"));
+        }
+        let mut expr = parse_expression(&expr_text, self.symbols, self.line_num, base + start)?;
+        
+        let mut spec_start = expr_end;
+        if chars.get(expr_end) == Some(&'!') {
+            let conversion_end = chars[expr_end..end].iter().position(|c| *c == ':').map_or(end, |p| expr_end + p);
+            let conversion: String = chars[expr_end + 1..conversion_end].iter().collect();
+            if conversion != "s" {
+                return Err(TranspilerError::at(
+                    &format!("Преобразование !{} в f-строке не поддерживается", conversion),
+                    char_span(expr_end, conversion_end),
+                )
+                .with_code("unsupported-format")
+                .with_help("поддерживается только !s"));
+            }
+            spec_start = conversion_end;
+        }
+        
+        let spec = if spec_start < end {
+            // Первый символ - двоеточие
+            let spec_text: String = chars[spec_start + 1..end].iter().collect();
+            let spec_column = base + spec_start + 1;
+            // Числовые форматы f требуют вещественного значения: целое сначала приводится к double
+            if spec_text.ends_with(['f', 'F']) && is_integer_type(expr.get_type()) {
+                expr = Expression::Conversion {
+                    span: expr.span(),
+                    value: Box::new(expr),
+                    expr_type: "double".to_string(),
+                };
+            }
+            translate_format_spec(&spec_text, expr.get_type(), self.line_num, spec_column)?
+        } else {
+            String::new()
+        };
+        
+        Ok(FormatPart::Value { expr, spec })
     }
     
-    // Если выражение в скобках
-    if trimmed.starts_with('(') && trimmed.ends_with(')') {
-        let inner = &trimmed[1..trimmed.len()-1].trim();
-        return parse_binary_expression(inner, variables, line_num, column + 1);
+    // Вызов функции с проверкой типов аргументов
+    fn parse_call(&mut self, name: String, name_token: &Token) -> Result<Expression, TranspilerError> {
+        // Встроенная функция, если имя не занято объявлением программы
+        if name == "len" && self.symbols.lookup(&name).is_none() {
+            return self.parse_len(name_token);
+        }
+        if name == "set" && self.symbols.lookup(&name).is_none() {
+            return self.parse_set_call(name_token);
+        }
+        if is_bytes_type(&name) {
+            return self.parse_bytes_call(name, name_token);
+        }
+        
+        let function_type = match self.symbols.get_type(&name) {
+            Some(type_name) => type_name.to_string(),
+            None => {
+                return Err(self.error_at(&format!("Функция '{}' не объявлена", name), name_token).with_code("undeclared-name"));
+            }
+        };
+        
+        let (param_types, return_type) = match parse_function_type(&function_type) {
+            Some(signature) => signature,
+            None => {
+                return Err(self.error_at(
+                    &format!("'{}' имеет тип {} и не является функцией", name, function_type),
+                    name_token,
+                )
+                .with_code("type-mismatch"));
+            }
+        };
+        
+        let (args, close_token) = self.parse_arguments()?;
+        let span = self.span_of(name_token).to(self.span_of(&close_token));
+        
+        if args.len() != param_types.len() {
+            return Err(self.error_at(
+                &format!(
+                    "Функция '{}' принимает {} аргумент(ов), передано {}",
+                    name,
+                    param_types.len(),
+                    args.len()
+                ),
+                name_token,
+            )
+            .with_code("argument-count"));
+        }
+        
+        let args = self.check_arguments(&format!("функции '{}'", name), args, &param_types)?;
+        
+        Ok(Expression::Call {
+            name,
+            args,
+            expr_type: return_type,
+            span,
+        })
     }
     
-    // Атомарное выражение
-    parse_atomic_expression(trimmed, variables, line_num, column)
+    // Логические and/or допустимы только для bool
+    fn make_logical(
+        &self,
+        op: BinaryOperator,
+        left: Expression,
+        right: Expression,
+    ) -> Result<Expression, TranspilerError> {
+        for operand in [&left, &right] {
+            if operand.get_type() != "bool" {
+                return Err(TranspilerError::at(
+                    &format!("Оператор {} допустим только для операндов типа bool, получен {}", op.as_str(), operand.get_type()),
+                    operand.span(),
+                )
+                .with_code("type-mismatch"));
+            }
+        }
+        
+        Ok(Expression::BinaryOp {
+            span: left.span().to(right.span()),
+            left: Box::new(left),
+            op,
+            right: Box::new(right),
+            expr_type: "bool".to_string(),
+        })
+    }
 }
 
-// Парсинг атомарного выражения (переменная или литерал)
-fn parse_atomic_expression(
-    expr: &str,
-    variables: &HashMap<String, String>,
-    line_num: usize,
-    column: usize,
-) -> Result<Expression, TranspilerError> {
-    // Переменная
-    if variables.contains_key(expr) {
-        let expr_type = variables.get(expr).unwrap().clone();
-        return Ok(Expression::Variable {
-            name: expr.to_string(),
-            expr_type,
-        });
+// Позиция закрывающей '}' подстановки f-строки с учётом вложенных скобок и строк
+fn find_field_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    for (i, &c) in chars.iter().enumerate().skip(start) {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'') => quote = Some(c),
+            (None, '(' | '[' | '{') => depth += 1,
+            (None, ')' | ']') => depth -= 1,
+            (None, '}') if depth == 0 => return Some(i),
+            (None, '}') => depth -= 1,
+            _ => {}
+        }
     }
-    
-    // Литерал
-    parse_literal(expr, line_num, column)
+    None
 }
 
-// Парсинг литерала
-fn parse_literal(expr: &str, line_num: usize, column: usize) -> Result<Expression, TranspilerError> {
-    let trimmed = expr.trim();
+// Конец выражения в подстановке f-строки: ! (кроме !=) или : вне скобок
+fn top_level_field_end(chars: &[char], start: usize, end: usize) -> usize {
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    for i in start..end {
+        match (quote, chars[i]) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'') => quote = Some('\''),
+            (None, '(' | '[' | '{') => depth += 1,
+            (None, ')' | ']' | '}') => depth -= 1,
+            (None, '!') if depth == 0 && chars.get(i + 1) != Some(&'=') => return i,
+            (None, ':') if depth == 0 => return i,
+            _ => {}
+        }
+    }
+    end
+}
+
+// Перевод спецификатора формата Python ([[fill]align][sign][#][0][width][.precision][type])
+// в спецификатор Rust; column - колонка первого символа спецификатора
+fn translate_format_spec(spec: &str, value_type: &str, line_num: usize, column: usize) -> Result<String, TranspilerError> {
+    let chars: Vec<char> = spec.chars().collect();
+    let unsupported = |message: String, i: usize| {
+        TranspilerError::at(&message, Span::new(line_num, column + i, column + i + 1)).with_code("unsupported-format")
+    };
+    let is_align = |c: Option<&char>| matches!(c, Some('<' | '>' | '^' | '='));
+    let is_number = is_numeric_type(value_type);
     
-    // Целое число
-    if let Ok(_) = trimmed.parse::<i64>() {
-        return Ok(Expression::Literal {
-            value: trimmed.to_string(),
-            expr_type: "int".to_string(),
-        });
+    let mut result = String::new();
+    let mut i = 0;
+    
+    // Заполнитель и выравнивание
+    let align_pos = if is_align(chars.get(1)) {
+        Some(1)
+    } else if is_align(chars.first()) {
+        Some(0)
+    } else {
+        None
+    };
+    if let Some(pos) = align_pos {
+        if chars[pos] == '=' {
+            return Err(unsupported("Выравнивание '=' не поддерживается".to_string(), pos));
+        }
+        if pos == 1 && matches!(chars[0], '{' | '}') {
+            return Err(unsupported("Фигурная скобка не может быть заполнителем".to_string(), 0));
+        }
+        result.extend(&chars[..=pos]);
+        i = pos + 1;
     }
     
-    // Число с плавающей точкой
-    if let Ok(_) = trimmed.parse::<f64>() {
-        return Ok(Expression::Literal {
-            value: trimmed.to_string(),
-            expr_type: "float".to_string(),
-        });
+    // Знак, альтернативная форма и заполнение нулями допустимы только для чисел
+    while let Some(&c) = chars.get(i) {
+        if !matches!(c, '+' | '-' | ' ' | '#' | 'z' | '0') {
+            break;
+        }
+        if !is_number {
+            return Err(unsupported(format!("'{}' в спецификаторе формата недопустимо для типа {}", c, value_type), i));
+        }
+        match c {
+            ' ' | 'z' => return Err(unsupported(format!("Флаг '{}' в спецификаторе формата не поддерживается", c), i)),
+            '-' => {}
+            _ => result.push(c),
+        }
+        i += 1;
+        if c == '0' {
+            break;
+        }
     }
     
-    // Булево значение
-    if trimmed == "True" {
-        return Ok(Expression::Literal {
-            value: "true".to_string(),
-            expr_type: "bool".to_string(),
-        });
+    // Ширина
+    while let Some(&c) = chars.get(i).filter(|c| c.is_ascii_digit()) {
+        result.push(c);
+        i += 1;
     }
-    if trimmed == "False" {
-        return Ok(Expression::Literal {
-            value: "false".to_string(),
-            expr_type: "bool".to_string(),
-        });
+    if let Some(&c) = chars.get(i).filter(|c| matches!(c, ',' | '_')) {
+        return Err(unsupported(format!("Разделитель разрядов '{}' не поддерживается", c), i));
     }
     
-    // None
-    if trimmed == "None" {
-        return Ok(Expression::Literal {
-            value: "()".to_string(),
-            expr_type: "None".to_string(),
-        });
+    // Точность
+    let precision_pos = i;
+    let mut has_precision = false;
+    if chars.get(i) == Some(&'.') {
+        let digits = chars[i + 1..].iter().take_while(|c| c.is_ascii_digit()).count();
+        if digits == 0 {
+            return Err(unsupported("После '.' ожидается точность".to_string(), i));
+        }
+        if is_integer_type(value_type) {
+            return Err(unsupported(format!("Точность недопустима для типа {}", value_type), i));
+        }
+        result.extend(&chars[i..=i + digits]);
+        i += digits + 1;
+        has_precision = true;
     }
     
-    // Байтовая строка (bytes)
-    if trimmed.starts_with("b\"") && trimmed.ends_with('"') {
-        let inner = &trimmed[2..trimmed.len()-1]; // Убираем b" и "
-        let escaped = crate::types::escape_string_for_rust(inner);
-        return Ok(Expression::Literal {
-            value: format!("b\"{}\"", escaped),
-            expr_type: "bytes".to_string(),
-        });
+    // Тип представления
+    if let Some(&c) = chars.get(i) {
+        let expected = match c {
+            'd' | 'x' | 'X' | 'o' | 'b' if is_integer_type(value_type) => None,
+            'f' | 'F' if is_number && !is_integer_type(value_type) => None,
+            's' if !is_number => None,
+            'd' | 'x' | 'X' | 'o' | 'b' => Some("целого числа"),
+            'f' | 'F' => Some("числа"),
+            's' => Some("строки"),
+            _ => return Err(unsupported(format!("Тип формата '{}' не поддерживается", c), i)),
+        };
+        if let Some(expected) = expected {
+            return Err(unsupported(format!("Тип формата '{}' требует {}, получен {}", c, expected, value_type), i));
+        }
+        match c {
+            'x' | 'X' | 'o' | 'b' => result.push(c),
+            // Без точности формат f выводит шесть знаков после запятой
+            'f' | 'F' if !has_precision => result.push_str(".6"),
+            _ => {}
+        }
+        i += 1;
+    } else if has_precision && is_number {
+        // Без типа точность в Python задаёт число значащих цифр
+        return Err(unsupported("Точность без типа формата не поддерживается".to_string(), precision_pos)
+            .with_help("укажите тип f, например .2f"));
     }
     
-    // Bytearray (преобразуем в Vec<u8>)
-    // В Pando для bytearray тоже можно использовать b"...", но будет преобразовано в .to_vec()
-    // Но для простоты пока оставляем так же
+    if i < chars.len() {
+        return Err(unsupported("Некорректный спецификатор формата".to_string(), i));
+    }
+    let is_plain_type = matches!(value_type, "bytes" | "bytearray" | "None")
+        || list_element_type(value_type).is_some()
+        || set_element_type(value_type).is_some()
+        || tuple_element_types(value_type).is_some()
+        || dict_types(value_type).is_some();
+    if !result.is_empty() && is_plain_type {
+        return Err(unsupported(format!("Спецификатор формата недопустим для типа {}", value_type), 0));
+    }
     
-    // Строка
-    if trimmed.starts_with('"') && trimmed.ends_with('"') {
-        let inner = &trimmed[1..trimmed.len()-1];
-        let escaped = crate::types::escape_string_for_rust(inner);
-        return Ok(Expression::Literal {
-            value: format!("\"{}\"", escaped),
-            expr_type: "str".to_string(),
-        });
+    Ok(result)
+}
+
+// Тип целочисленного литерала без контекста: int, а если значение не помещается - более широкий тип
+fn integer_literal_type(value: &str) -> Option<&'static str> {
+    let value = value.parse::<u128>().ok()?;
+    let expr_type = if value <= i32::MAX as u128 {
+        "int"
+    } else if value <= i64::MAX as u128 {
+        "int64"
+    } else if value <= i128::MAX as u128 {
+        "int128"
+    } else {
+        "uint128"
+    };
+    Some(expr_type)
+}
+
+// Приведение выражения к ожидаемому типу без явного преобразования: числовая константа
+// принимает тип из контекста, значение str копируется в string, а bytes - в bytearray,
+// литералы списка, множества, кортежа и словаря приводятся поэлементно.
+// Если привести нельзя, выражение возвращается как есть и несовпадение сообщает вызывающий
+pub fn coerce_expression(expr: Expression, target: &str) -> Result<Expression, TranspilerError> {
+    if expr.get_type() == target {
+        return Ok(expr);
     }
     
-    // Символ
-    if trimmed.starts_with('\'') && trimmed.ends_with('\'') && trimmed.len() >= 3 {
-        let inner = &trimmed[1..trimmed.len()-1];
-        let escaped = crate::types::escape_string_for_rust(inner);
-        return Ok(Expression::Literal {
-            value: format!("'{}'", escaped),
-            expr_type: "char".to_string(),
-        });
+    match expr {
+        // Элементы литерала списка приводятся к типу элементов целевого списка
+        Expression::List { elements, expr_type, span } if list_element_type(target).is_some() => {
+            let element_type = list_element_type(target).unwrap_or_default();
+            let elements = elements
+                .into_iter()
+                .map(|element| coerce_expression(element, element_type))
+                .collect::<Result<Vec<_>, _>>()?;
+            let expr_type = if elements.iter().all(|element| element.get_type() == element_type) {
+                target.to_string()
+            } else {
+                expr_type
+            };
+            Ok(Expression::List { elements, expr_type, span })
+        }
+        // Элементы литерала множества приводятся к типу элементов целевого множества
+        Expression::Set { elements, expr_type, span } if set_element_type(target).is_some() => {
+            let element_type = set_element_type(target).unwrap_or_default();
+            let elements = elements
+                .into_iter()
+                .map(|element| coerce_expression(element, element_type))
+                .collect::<Result<Vec<_>, _>>()?;
+            let expr_type = if elements.iter().all(|element| element.get_type() == element_type) {
+                target.to_string()
+            } else {
+                expr_type
+            };
+            Ok(Expression::Set { elements, expr_type, span })
+        }
+        // Элементы литерала кортежа приводятся к типам соответствующих элементов
+        Expression::Tuple { elements, expr_type, span } => {
+            let Some(element_types) = tuple_element_types(target).filter(|types| types.len() == elements.len()) else {
+                return Ok(Expression::Tuple { elements, expr_type, span });
+            };
+            let elements = elements
+                .into_iter()
+                .zip(&element_types)
+                .map(|(element, element_type)| coerce_expression(element, element_type))
+                .collect::<Result<Vec<_>, _>>()?;
+            let actual_types: Vec<String> = elements.iter().map(|e| e.get_type().to_string()).collect();
+            Ok(Expression::Tuple { elements, expr_type: tuple_type(&actual_types), span })
+        }
+        // Ключи и значения литерала словаря приводятся к типам целевого словаря
+        Expression::Dict { entries, expr_type, span } if dict_types(target).is_some() => {
+            let (key_type, value_type) = dict_types(target).unwrap_or_default();
+            let entries = entries
+                .into_iter()
+                .map(|(key, value)| Ok((coerce_expression(key, &key_type)?, coerce_expression(value, &value_type)?)))
+                .collect::<Result<Vec<_>, TranspilerError>>()?;
+            let expr_type = if entries.iter().all(|(key, value)| key.get_type() == key_type && value.get_type() == value_type) {
+                target.to_string()
+            } else {
+                expr_type
+            };
+            Ok(Expression::Dict { entries, expr_type, span })
+        }
+        expr if (expr.get_type() == "str" && target == "string") || (expr.get_type() == "bytes" && target == "bytearray") => Ok(Expression::Conversion {
+            span: expr.span(),
+            value: Box::new(expr),
+            expr_type: target.to_string(),
+        }),
+        expr if expr.is_constant() && can_retype_constant(&expr, target) => {
+            let retyped = retype_constant(expr, target, None)?;
+            evaluate_constant(&retyped)?;
+            Ok(retyped)
+        }
+        expr => Ok(expr),
     }
+}
+
+// Общий тип элементов коллекции (what - начало сообщения об ошибке): тип задаёт первый элемент,
+// к типу которого приводятся все остальные; неконстантные элементы проверяются первыми,
+// как у операндов бинарных операций, а пустые коллекции без типа - последними
+fn common_type(elements: &[Expression], what: &str) -> Result<String, TranspilerError> {
+    let mut candidates: Vec<&Expression> = elements.iter().filter(|e| !e.is_constant()).collect();
+    candidates.extend(elements.iter().filter(|e| e.is_constant()));
+    candidates.sort_by_key(|e| e.get_type().contains('?'));
+    let common_type = candidates
+        .iter()
+        .map(|e| e.get_type())
+        .find(|t| elements.iter().all(|e| coerce_expression(e.clone(), t).is_ok_and(|e| e.get_type() == *t)))
+        .map(|t| t.to_string());
     
-    // Если это идентификатор для bytearray (например, Vec::new())
-    if trimmed == "Vec::new()" || trimmed == "vec![]" {
-        return Ok(Expression::Literal {
-            value: "Vec::new()".to_string(),
-            expr_type: "bytearray".to_string(),
-        });
+    let Some(common_type) = common_type else {
+        let first = &elements[0];
+        let other = elements.iter().find(|e| e.get_type() != first.get_type()).unwrap_or(first);
+        return Err(TranspilerError::at(
+            &format!("{} имеют несовместимые типы: {} и {}", what, first.get_type(), other.get_type()),
+            other.span(),
+        )
+        .with_code("type-mismatch")
+        .with_label(first.span(), &format!("имеет тип {}", first.get_type())));
+    };
+    if common_type == "None" || parse_function_type(&common_type).is_some() {
+        return Err(TranspilerError::at(
+            &format!("Значение типа {} не может храниться в коллекции", common_type),
+            elements[0].span(),
+        )
+        .with_code("type-mismatch"));
+    }
+    Ok(common_type)
+}
+
+// Проверка, что значения типа могут быть элементами множества
+fn check_set_element(element_type: &str, span: Span) -> Result<(), TranspilerError> {
+    if is_hashable_type(element_type) {
+        return Ok(());
+    }
+    Err(TranspilerError::at(&format!("Тип {} не может быть элементом множества", element_type), span)
+        .with_code("type-mismatch")
+        .with_note("элементом может быть целое число, bool, char, строка или bytes"))
+}
+
+// Операнд без побочных эффектов можно вычислять повторно: переменная или константа
+fn is_side_effect_free(expr: &Expression) -> bool {
+    matches!(expr, Expression::Variable { .. } | Expression::Literal { .. }) || expr.is_constant()
+}
+
+// Согласование типов операндов: константа принимает тип другого операнда,
+// из двух констант приводится целая к вещественной или int к более широкому типу,
+// из строк str и string - str к string, из байтов bytes и bytearray - bytes к bytearray
+fn unify_operands(left: Expression, right: Expression) -> Result<(Expression, Expression), TranspilerError> {
+    let left_type = left.get_type().to_string();
+    let right_type = right.get_type().to_string();
+    if left_type == right_type {
+        return Ok((left, right));
+    }
+    if is_text_type(&left_type) && is_text_type(&right_type) {
+        return match left_type.as_str() {
+            "str" => Ok((coerce_expression(left, "string")?, right)),
+            _ => Ok((left, coerce_expression(right, "string")?)),
+        };
+    }
+    if is_bytes_type(&left_type) && is_bytes_type(&right_type) {
+        return match left_type.as_str() {
+            "bytes" => Ok((coerce_expression(left, "bytearray")?, right)),
+            _ => Ok((left, coerce_expression(right, "bytearray")?)),
+        };
     }
     
-    Err(TranspilerError::new(
-        &format!("Некорректный литерал: {}", trimmed),
-        line_num,
-        column,
-    ))
-}
-
-// Парсинг составного присваивания
-fn parse_compound_assignment(expr: &str) -> Option<(String, BinaryOperator, String)> {
-    let compound_ops = [
-        ("+=", BinaryOperator::Add),
-        ("-=", BinaryOperator::Subtract),
-        ("*=", BinaryOperator::Multiply),
-        ("/=", BinaryOperator::Divide),
-        ("//=", BinaryOperator::FloorDivide),
-        ("%=", BinaryOperator::Modulo),
-        ("|=", BinaryOperator::BitwiseOr),
-        ("&=", BinaryOperator::BitwiseAnd),
-        ("^=", BinaryOperator::BitwiseXor),
-    ];
-    
-    for (op_str, op) in &compound_ops {
-        if let Some(pos) = expr.find(op_str) {
-            let left = &expr[..pos].trim();
-            let right = &expr[pos + op_str.len()..].trim();
-            
-            if !left.is_empty() && !right.is_empty() {
-                return Some((left.to_string(), *op, right.to_string()));
+    let coerce_left = match (left.is_constant(), right.is_constant()) {
+        (true, false) => true,
+        (false, true) => false,
+        (true, true) if is_integer_type(&left_type) != is_integer_type(&right_type) => is_integer_type(&left_type),
+        (true, true) => left_type == "int" || left_type == "float",
+        (false, false) => matches!(
+            left,
+            Expression::Literal { .. }
+                | Expression::List { .. }
+                | Expression::Set { .. }
+                | Expression::Tuple { .. }
+                | Expression::Dict { .. }
+        ),
+    };
+    
+    if coerce_left {
+        Ok((coerce_expression(left, &right_type)?, right))
+    } else {
+        Ok((left, coerce_expression(right, &left_type)?))
+    }
+}
+
+// Можно ли вычислить константу в целевом типе: вещественная не становится целой,
+// а все операции должны быть допустимы для нового типа
+fn can_retype_constant(expr: &Expression, target: &str) -> bool {
+    match expr {
+        Expression::Literal { expr_type, .. } => {
+            is_numeric_type(target) && (is_integer_type(expr_type) || !is_integer_type(target))
+        }
+        Expression::UnaryOp { op, expr, .. } => {
+            (*op == UnaryOperator::Negate || is_bitwise_type(target)) && can_retype_constant(expr, target)
+        }
+        Expression::BinaryOp { left, op, right, .. } => {
+            is_operator_valid_for_type(*op, target)
+                && can_retype_constant(left, target)
+                && can_retype_constant(right, target)
+        }
+        _ => false,
+    }
+}
+
+// Перевод константы в целевой тип с проверкой диапазона литералов;
+// negation - положение унарного минуса, под которым стоит литерал
+fn retype_constant(expr: Expression, target: &str, negation: Option<Span>) -> Result<Expression, TranspilerError> {
+    let expr_type = target.to_string();
+    let retyped = match expr {
+        Expression::Literal { value, span, .. } => {
+            check_literal_range(&value, negation.is_some(), target, negation.unwrap_or(span))?;
+            let is_integer_text = !value.contains(['.', 'e', 'E']);
+            let value = if is_integer_text && !is_integer_type(target) {
+                format!("{}.0", value)
+            } else {
+                value
+            };
+            Expression::Literal { value, expr_type, span }
+        }
+        Expression::UnaryOp { op, expr, span, .. } => {
+            let is_negate = op == UnaryOperator::Negate;
+            let is_unsigned = integer_range(target).is_some_and(|(min, _)| min == 0);
+            if is_negate && is_unsigned && !matches!(*expr, Expression::Literal { .. }) {
+                return Err(TranspilerError::at(
+                    &format!("Отрицательное значение недопустимо для типа {}", target),
+                    span,
+                )
+                .with_code("invalid-range"));
+            }
+            Expression::UnaryOp {
+                op,
+                expr: Box::new(retype_constant(*expr, target, (is_negate && negation.is_none()).then_some(span))?),
+                expr_type,
+                span,
             }
         }
+        Expression::BinaryOp { left, op, right, span, .. } => Expression::BinaryOp {
+            left: Box::new(retype_constant(*left, target, None)?),
+            op,
+            right: Box::new(retype_constant(*right, target, None)?),
+            expr_type,
+            span,
+        },
+        other => other,
+    };
+    Ok(retyped)
+}
+
+// Проверка, что целочисленный литерал помещается в целевой тип
+fn check_literal_range(value: &str, negated: bool, target: &str, span: Span) -> Result<(), TranspilerError> {
+    let Some((min, max)) = integer_range(target) else {
+        return Ok(());
+    };
+    let fits = value.parse::<u128>().is_ok_and(|v| if negated { v <= min } else { v <= max });
+    if fits {
+        return Ok(());
     }
     
-    None
+    let sign = if negated { "-" } else { "" };
+    Err(range_error(&format!("{}{}", sign, value), target, span))
+}
+
+// Ошибка выхода значения за диапазон типа
+fn range_error(value: &str, target: &str, span: Span) -> TranspilerError {
+    let range = match integer_range(target) {
+        Some((0, max)) => format!("от 0 до {}", max),
+        Some((min, max)) => format!("от -{} до {}", min, max),
+        None if target == "float" => format!("по модулю до {:e}", f32::MAX),
+        None => format!("по модулю до {:e}", f64::MAX),
+    };
+    TranspilerError::at(&format!("Значение {} не помещается в тип {}", value, target), span)
+        .with_code("invalid-range")
+        .with_note(&format!("допустимый диапазон {}: {}", target, range))
+}
+
+// Значение константы, вычисленное при трансляции
+#[derive(Debug, Clone, Copy)]
+enum ConstantValue {
+    Integer(i128),
+    Float(f64),
+}
+
+// Константа типа по умолчанию, значение которой не помещается в тип, расширяется,
+// как литерал: int до int64 и int128, float до double
+fn widen_constant(mut expr: Expression) -> Result<Expression, TranspilerError> {
+    loop {
+        let error = match evaluate_constant(&expr) {
+            Ok(_) => return Ok(expr),
+            Err(error) => error,
+        };
+        let wider = match expr.get_type() {
+            "int" => "int64",
+            "int64" => "int128",
+            "float" => "double",
+            _ => return Err(error),
+        };
+        if error.code != Some("invalid-range") {
+            return Err(error);
+        }
+        expr = retype_constant(expr, wider, None)?;
+    }
+}
+
+// Вычисление константы так, как её вычислит программа: каждая операция выполняется
+// в типе своего узла, и значение каждого узла должно помещаться в этот тип.
+// None - значение при трансляции не вычисляется (литерал uint128 больше i128)
+fn evaluate_constant(expr: &Expression) -> Result<Option<ConstantValue>, TranspilerError> {
+    let expr_type = expr.get_type();
+    let span = expr.span();
+    let value = match expr {
+        Expression::Literal { value, .. } if is_integer_type(expr_type) => value.parse().ok().map(ConstantValue::Integer),
+        Expression::Literal { value, .. } => value.parse().ok().map(ConstantValue::Float),
+        // Литерал под минусом проверен вместе со знаком: -2147483648 помещается в int
+        Expression::UnaryOp { op: UnaryOperator::Negate, expr: inner, .. } if matches!(**inner, Expression::Literal { .. }) && is_integer_type(expr_type) => {
+            match &**inner {
+                Expression::Literal { value, .. } => value.parse::<i128>().ok().map(|value| ConstantValue::Integer(-value)),
+                _ => None,
+            }
+        }
+        Expression::UnaryOp { op, expr: inner, .. } => match (evaluate_constant(inner)?, op) {
+            (Some(ConstantValue::Integer(value)), UnaryOperator::Negate) => {
+                Some(ConstantValue::Integer(value.checked_neg().ok_or_else(|| overflow_error(expr_type, span))?))
+            }
+            // В беззнаковом типе инверсия отсчитывается от наибольшего значения
+            (Some(ConstantValue::Integer(value)), _) => match integer_range(expr_type) {
+                Some((0, max)) => i128::try_from(max).ok().map(|max| ConstantValue::Integer(max - value)),
+                _ => Some(ConstantValue::Integer(!value)),
+            },
+            (Some(ConstantValue::Float(value)), _) => Some(ConstantValue::Float(-value)),
+            (None, _) => None,
+        },
+        Expression::BinaryOp { left, op, right, .. } => {
+            let operands = (evaluate_constant(left)?, evaluate_constant(right)?);
+            match operands {
+                (Some(ConstantValue::Integer(a)), Some(ConstantValue::Integer(b))) if is_integer_type(expr_type) => {
+                    let value = evaluate_integer_operation(*op, a, b, expr_type, right.span())?;
+                    Some(ConstantValue::Integer(value.ok_or_else(|| overflow_error(expr_type, span))?))
+                }
+                (Some(a), Some(b)) => Some(ConstantValue::Float(evaluate_float_operation(*op, as_float(a), as_float(b), right.span())?)),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    
+    match value {
+        Some(ConstantValue::Integer(value)) => {
+            let fits = integer_range(expr_type).is_some_and(|(min, max)| {
+                if value < 0 { value.unsigned_abs() <= min } else { value as u128 <= max }
+            });
+            if !fits {
+                return Err(range_error(&value.to_string(), expr_type, span));
+            }
+        }
+        Some(ConstantValue::Float(value)) => {
+            let limit = if expr_type == "float" { f32::MAX as f64 } else { f64::MAX };
+            if !value.is_finite() || value.abs() > limit {
+                return Err(range_error(&format!("{:e}", value), expr_type, span));
+            }
+        }
+        None => {}
+    }
+    Ok(value)
+}
+
+fn as_float(value: ConstantValue) -> f64 {
+    match value {
+        ConstantValue::Integer(value) => value as f64,
+        ConstantValue::Float(value) => value,
+    }
 }
 
-// Проверка оператора на позиции
-fn check_operator_at_position(expr: &str, pos: usize, op_str: &str) -> bool {
-    if pos + op_str.len() > expr.len() {
-        return false;
+// Значение не помещается даже в 128 бит
+fn overflow_error(target: &str, span: Span) -> TranspilerError {
+    range_error("выражения", target, span)
+}
+
+// Операция над целыми константами по правилам сгенерированного кода: // и % округляют вниз,
+// как в Python, а / отбрасывает дробную часть. None - переполнение 128 бит
+fn evaluate_integer_operation(
+    op: BinaryOperator,
+    a: i128,
+    b: i128,
+    expr_type: &str,
+    divisor_span: Span,
+) -> Result<Option<i128>, TranspilerError> {
+    if b == 0 && matches!(op, BinaryOperator::Divide | BinaryOperator::FloorDivide | BinaryOperator::Modulo) {
+        return Err(TranspilerError::at("Деление на ноль", divisor_span).with_code("division-by-zero"));
+    }
+    let bits = integer_range(expr_type).map_or(128, |(min, max)| 128 - max.leading_zeros() + u32::from(min > 0));
+    let is_shift = matches!(op, BinaryOperator::ShiftLeft | BinaryOperator::ShiftRight);
+    if is_shift && (b < 0 || b >= i128::from(bits)) {
+        return Err(TranspilerError::at(&format!("Сдвиг на {} бит недопустим для типа {}", b, expr_type), divisor_span)
+            .with_code("invalid-range")
+            .with_note(&format!("величина сдвига для {}: от 0 до {}", expr_type, bits - 1)));
+    }
+    if op == BinaryOperator::Power && b < 0 {
+        return Err(TranspilerError::at("Отрицательный показатель степени", divisor_span)
+            .with_code("invalid-range")
+            .with_help("для дробного результата используйте вещественное основание: 2.0 ** -1"));
     }
     
-    &expr[pos..pos + op_str.len()] == op_str
+    let value = match op {
+        BinaryOperator::Add => a.checked_add(b),
+        BinaryOperator::Subtract => a.checked_sub(b),
+        BinaryOperator::Multiply => a.checked_mul(b),
+        BinaryOperator::Divide => a.checked_div(b),
+        BinaryOperator::FloorDivide => a.checked_div(b).map(|quotient| {
+            if a % b != 0 && (a < 0) != (b < 0) { quotient - 1 } else { quotient }
+        }),
+        BinaryOperator::Modulo => a.checked_rem(b).map(|remainder| {
+            if remainder != 0 && (remainder < 0) != (b < 0) { remainder + b } else { remainder }
+        }),
+        BinaryOperator::Power => u32::try_from(b).ok().and_then(|exponent| a.checked_pow(exponent)),
+        BinaryOperator::BitwiseAnd => Some(a & b),
+        BinaryOperator::BitwiseOr => Some(a | b),
+        BinaryOperator::BitwiseXor => Some(a ^ b),
+        BinaryOperator::ShiftLeft => 1i128.checked_shl(b as u32).filter(|factor| *factor > 0).and_then(|factor| a.checked_mul(factor)),
+        BinaryOperator::ShiftRight => Some(a >> b),
+        _ => None,
+    };
+    Ok(value)
+}
+
+// Операция над вещественными константами; деление на ноль - ошибка, как в Python
+fn evaluate_float_operation(op: BinaryOperator, a: f64, b: f64, divisor_span: Span) -> Result<f64, TranspilerError> {
+    if b == 0.0 && matches!(op, BinaryOperator::Divide | BinaryOperator::FloorDivide | BinaryOperator::Modulo) {
+        return Err(TranspilerError::at("Деление на ноль", divisor_span).with_code("division-by-zero"));
+    }
+    let value = match op {
+        BinaryOperator::Add => a + b,
+        BinaryOperator::Subtract => a - b,
+        BinaryOperator::Multiply => a * b,
+        BinaryOperator::Divide => a / b,
+        BinaryOperator::FloorDivide => (a / b).floor(),
+        BinaryOperator::Modulo => {
+            let remainder = a % b;
+            if remainder != 0.0 && (remainder < 0.0) != (b < 0.0) { remainder + b } else { remainder }
+        }
+        _ => a.powf(b),
+    };
+    Ok(value)
+}
+
+// Разделение вызова вида имя(аргументы) на имя и строку аргументов
+pub fn split_call(expr: &str) -> Option<(&str, &str)> {
+    let expr = expr.trim();
+    let open = expr.find('(')?;
+    let name = expr[..open].trim();
+    let is_identifier = name.chars().next().is_some_and(|c| c.is_alphabetic())
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+    if !is_identifier || !expr.ends_with(')') {
+        return None;
+    }
+    
+    // Скобка, открытая после имени, должна закрываться в конце выражения
+    let args = &expr[open + 1..expr.len() - 1];
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for c in args.chars() {
+        match (quote, c) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') if depth == 0 => return None,
+            (None, ')') => depth -= 1,
+            _ => {}
+        }
+    }
+    
+    Some((name, args))
+}
+
+// Позиции символов выражения вне скобок и строковых литералов
+pub fn top_level_positions(expr: &str) -> Vec<usize> {
+    let mut positions = Vec::new();
+    let mut paren_count = 0;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    
+    for (i, ch) in expr.char_indices() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == q {
+                quote = None;
+            }
+            continue;
+        }
+        
+        match ch {
+            '"' | '\'' => quote = Some(ch),
+            '(' | '[' | '{' => paren_count += 1,
+            ')' | ']' | '}' => paren_count -= 1,
+            _ if paren_count == 0 => positions.push(i),
+            _ => {}
+        }
+    }
+    
+    positions
+}
+
+// Разбиение списка аргументов по запятым верхнего уровня (со смещениями аргументов)
+pub fn split_arguments(args: &str) -> Vec<&str> {
+    if args.trim().is_empty() {
+        return Vec::new();
+    }
+    
+    let mut result = Vec::new();
+    let mut start = 0;
+    for pos in top_level_positions(args) {
+        if args[pos..].starts_with(',') {
+            result.push(args[start..pos].trim());
+            start = pos + 1;
+        }
+    }
+    result.push(args[start..].trim());
+    
+    result
 }
 
 // Проверка допустимости операции для типа
-fn is_operator_valid_for_type(op: BinaryOperator, type_name: &str) -> bool {
+pub fn is_operator_valid_for_type(op: BinaryOperator, type_name: &str) -> bool {
+    // Для множеств |, &, ^ и - означают объединение, пересечение,
+    // симметрическую разность и разность
+    if set_element_type(type_name).is_some() {
+        return matches!(
+            op,
+            BinaryOperator::BitwiseOr | BinaryOperator::BitwiseAnd | BinaryOperator::BitwiseXor | BinaryOperator::Subtract
+                | BinaryOperator::Equal | BinaryOperator::NotEqual | BinaryOperator::In | BinaryOperator::NotIn
+        );
+    }
     match op {
-        BinaryOperator::Add | BinaryOperator::Subtract | 
-        BinaryOperator::Multiply | BinaryOperator::Divide => {
+        BinaryOperator::Add => is_numeric_type(type_name) || type_name == "string" || is_bytes_type(type_name),
+        BinaryOperator::Subtract | 
+        BinaryOperator::Multiply | BinaryOperator::Divide |
+        BinaryOperator::FloorDivide | BinaryOperator::Modulo | BinaryOperator::Power => {
             is_numeric_type(type_name)
         }
-        BinaryOperator::FloorDivide | BinaryOperator::Modulo => {
-            is_integer_type(type_name)
-        }
         BinaryOperator::BitwiseOr | BinaryOperator::BitwiseAnd | 
-        BinaryOperator::BitwiseXor => {
+        BinaryOperator::BitwiseXor | BinaryOperator::ShiftLeft | BinaryOperator::ShiftRight => {
             is_bitwise_type(type_name)
         }
+        BinaryOperator::Equal | BinaryOperator::NotEqual => true,
+        BinaryOperator::Less | BinaryOperator::LessEqual |
+        BinaryOperator::Greater | BinaryOperator::GreaterEqual => {
+            is_ordered_type(type_name)
+        }
+        BinaryOperator::In | BinaryOperator::NotIn => {
+            list_element_type(type_name).is_some()
+                || dict_types(type_name).is_some()
+                || is_text_type(type_name)
+                || is_bytes_type(type_name)
+        }
+        BinaryOperator::And | BinaryOperator::Or => type_name == "bool",
     }
-}
\ No newline at end of file
+}
+
+// Сигнатура метода множества с элементами типа element
+fn set_method_signature(element: &str, method: &str) -> Option<(Vec<String>, usize, String)> {
+    match method {
+        "add" | "discard" => Some((vec![element.to_string()], 1, "None".to_string())),
+        _ => None,
+    }
+}
+
+// Сигнатура метода словаря с ключами типа key и значениями типа value
+fn dict_method_signature(key: &str, value: &str, method: &str) -> Option<(Vec<String>, usize, String)> {
+    let signature = match method {
+        "get" => (vec![key.to_string(), value.to_string()], 2, value.to_string()),
+        "keys" => (Vec::new(), 0, list_type(key)),
+        "values" => (Vec::new(), 0, list_type(value)),
+        "items" => (Vec::new(), 0, list_type(&tuple_type(&[key.to_string(), value.to_string()]))),
+        _ => return None,
+    };
+    Some(signature)
+}
+
+// Сигнатура метода списка с элементами типа element: типы параметров, число обязательных
+// параметров и тип результата; index - параметр-индекс любого целого типа
+fn list_method_signature(element: &str, method: &str) -> Option<(Vec<String>, usize, String)> {
+    let signature = match method {
+        "append" => (vec![element], 1, "None"),
+        "insert" => (vec!["index", element], 2, "None"),
+        "pop" => (vec!["index"], 0, element),
+        "remove" => (vec![element], 1, "None"),
+        _ => return None,
+    };
+    let (params, required, return_type) = signature;
+    Some((params.into_iter().map(str::to_string).collect(), required, return_type.to_string()))
+}
+
+// Сигнатура метода строки; text - параметр-строка str или string, texts - список строк.
+// Методы не изменяют строку и возвращают новое значение
+fn text_method_signature(method: &str) -> Option<(Vec<String>, usize, String)> {
+    let signature = match method {
+        "upper" | "lower" | "strip" => (vec![], 0, "string".to_string()),
+        "split" => (vec!["text"], 0, list_type("string")),
+        "join" => (vec!["texts"], 1, "string".to_string()),
+        "replace" => (vec!["text", "text"], 2, "string".to_string()),
+        "startswith" | "endswith" => (vec!["text"], 1, "bool".to_string()),
+        "find" => (vec!["text"], 1, "int".to_string()),
+        "encode" => (vec!["str"], 0, "bytes".to_string()),
+        _ => return None,
+    };
+    let (params, required, return_type) = signature;
+    Some((params.into_iter().map(str::to_string).collect(), required, return_type))
+}
+
+// Сигнатура метода bytes и bytearray; buffer - параметр-байты: bytes, bytearray или list[uint8].
+// extend есть только у bytearray, остальные методы списка bytearray берёт у list[uint8]
+fn bytes_method_signature(method: &str) -> Option<(Vec<String>, usize, String)> {
+    let signature = match method {
+        "extend" => (vec!["buffer"], 1, "None"),
+        "hex" => (vec![], 0, "string"),
+        "decode" => (vec!["str"], 0, "string"),
+        _ => return None,
+    };
+    let (params, required, return_type) = signature;
+    Some((params.into_iter().map(str::to_string).collect(), required, return_type.to_string()))
+}
+
+// Кодировка в encode и decode: поддерживается только UTF-8, и она должна быть указана литералом
+fn check_encoding(args: &[Expression]) -> Result<(), TranspilerError> {
+    let Some(encoding) = args.first() else {
+        return Ok(());
+    };
+    let is_utf8 = match encoding {
+        Expression::Literal { value, .. } => {
+            matches!(value.trim_matches('"').to_lowercase().replace('_', "-").as_str(), "utf-8" | "utf8")
+        }
+        _ => false,
+    };
+    if is_utf8 {
+        return Ok(());
+    }
+    Err(TranspilerError::at("Поддерживается только кодировка \"utf-8\"", encoding.span())
+        .with_code("unsupported-operation")
+        .with_note("кодировка указывается строковым литералом"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_expression;
+    use crate::generator::generate_expression;
+    use crate::symbols::SymbolTable;
+    use crate::types::Span;
+
+    // Разбор выражения с переменными a и b типа int; результат - сгенерированный код Rust и тип
+    fn parse(expr: &str) -> (String, String) {
+        let mut symbols = SymbolTable::default();
+        for name in ["a", "b"] {
+            symbols.declare_variable(name, "int", Span::new(1, 1, 2)).unwrap();
+        }
+        let expression = parse_expression(expr, &symbols, 1, 1).expect("выражение должно разбираться");
+        (generate_expression(&expression), expression.get_type().to_string())
+    }
+
+    // Унарный минус после бинарного оператора относится к следующему операнду
+    #[test]
+    fn unary_minus_after_operator() {
+        assert_eq!(parse("a - -b").0, "(a - (-b))");
+        assert_eq!(parse("2*-3").0, "(2 * (-3))");
+    }
+
+    // Операции одного приоритета левоассоциативны
+    #[test]
+    fn left_associative_subtraction() {
+        assert_eq!(parse("10 - 2 - 3").0, "((10 - 2) - 3)");
+        assert_eq!(parse("a - b + 1").0, "((a - b) + 1)");
+    }
+
+    // Литералы разбираются целиком: строка с оператором внутри и вещественное с экспонентой
+    #[test]
+    fn literals() {
+        assert_eq!(parse("\"a+b\""), ("\"a+b\"".to_string(), "str".to_string()));
+        assert_eq!(parse("1e-5"), ("1e-5".to_string(), "float".to_string()));
+    }
+
+    // Умножение связывает сильнее сложения, скобки меняют порядок
+    #[test]
+    fn precedence_and_parentheses() {
+        assert_eq!(parse("a + b * 2").0, "(a + (b * 2))");
+        assert_eq!(parse("(a + b) * 2").0, "((a + b) * 2)");
+    }
+}
@@ -1,137 +1,1373 @@
-use crate::types::{ParsedLine, Expression, BinaryOperator, UnaryOperator, get_type_mapping, get_default_value};
+use crate::types::{ParsedLine, Block, ForIterable, Expression, FormatPart, BinaryOperator, UnaryOperator, get_type_mapping, escape_string_for_rust, is_copy_type, is_integer_type, is_integer_widening, integer_range, constant_integer, is_text_type, is_bytes_type, list_element_type, set_element_type, tuple_element_types, dict_types};
+
+// Соответствие бинарных операторов Pando операторам Rust
+fn rust_binary_operator(op: &BinaryOperator) -> &'static str {
+    match op {
+        BinaryOperator::Add => "+",
+        BinaryOperator::Subtract => "-",
+        BinaryOperator::Multiply => "*",
+        BinaryOperator::Divide => "/",
+        BinaryOperator::FloorDivide => "/", // Совпадает с // только для беззнаковых, см. generate_binary_operation
+        BinaryOperator::Power => "**", // В Rust нет оператора степени, генерируется вызов pow
+        BinaryOperator::Modulo => "%",
+        BinaryOperator::BitwiseOr => "|",
+        BinaryOperator::BitwiseAnd => "&",
+        BinaryOperator::BitwiseXor => "^",
+        BinaryOperator::ShiftLeft => "<<",
+        BinaryOperator::ShiftRight => ">>",
+        BinaryOperator::Equal => "==",
+        BinaryOperator::NotEqual => "!=",
+        BinaryOperator::Less => "<",
+        BinaryOperator::LessEqual => "<=",
+        BinaryOperator::Greater => ">",
+        BinaryOperator::GreaterEqual => ">=",
+        BinaryOperator::In | BinaryOperator::NotIn => "in", // Генерируется вызов contains
+        BinaryOperator::And => "&&",
+        BinaryOperator::Or => "||",
+    }
+}
+
+// Тип Rust для типа Pando
+fn rust_type(type_name: &str) -> String {
+    get_type_mapping(type_name).unwrap_or_else(|| "i32".to_string())
+}
+
+// Тип в сигнатуре функции. Ссылки &str получают общее время жизни 'a: иначе при нескольких
+// ссылках в параметрах (dict[str, int] содержит две) Rust не может вывести его для результата
+fn signature_type(type_name: &str) -> String {
+    rust_type(type_name).replace("&str", "&'a str")
+}
 
 // Функция для генерации Rust кода из выражения
 pub fn generate_expression(expr: &Expression) -> String {
     match expr {
-        Expression::Literal { value, expr_type } => {
-            // Для bytearray, если значение начинается с b" и тип bytearray, нужно добавить .to_vec()
-            if expr_type == "bytearray" && value.starts_with("b\"") {
-                format!("{}.to_vec()", value)
-            } else {
-                value.clone()
+        // Литерал bytes копируется в Vec<u8>; для чтения на месте generate_place оставляет b"..."
+        Expression::Literal { value, expr_type, .. } if expr_type == "bytes" => format!("{}.to_vec()", value),
+        Expression::Literal { value, .. } => value.clone(),
+        Expression::Variable { name, .. } => name.clone(),
+        // Проверка вхождения - поиск в списке, среди ключей словаря или подстроки в строке
+        Expression::BinaryOp { left, op: op @ (BinaryOperator::In | BinaryOperator::NotIn), right, .. } => {
+            let negation = if *op == BinaryOperator::NotIn { "!" } else { "" };
+            if is_text_type(right.get_type()) {
+                let pattern = if left.get_type() == "char" { generate_expression(left) } else { generate_str(left) };
+                return format!("({}{}.contains({}))", negation, generate_receiver(right), pattern);
             }
+            if dict_types(right.get_type()).is_some() {
+                return format!("({}{}.0.contains_key(&{}))", negation, generate_place(right), generate_expression(left));
+            }
+            format!("({}{}.contains(&{}))", negation, generate_place(right), generate_expression(left))
         }
-        Expression::Variable { name, .. } => name.clone(),
-        Expression::BinaryOp { left, op, right, .. } => {
-            let left_expr = generate_expression(left);
-            let right_expr = generate_expression(right);
-            let op_str = match op {
-                BinaryOperator::Add => "+",
-                BinaryOperator::Subtract => "-",
-                BinaryOperator::Multiply => "*",
-                BinaryOperator::Divide => "/",
-                BinaryOperator::FloorDivide => "/", // В Rust целочисленное деление
-                BinaryOperator::Modulo => "%",
-                BinaryOperator::BitwiseOr => "|",
-                BinaryOperator::BitwiseAnd => "&",
-                BinaryOperator::BitwiseXor => "^",
-            };
-            format!("({} {} {})", left_expr, op_str, right_expr)
+        // Словари равны при одинаковых парах независимо от порядка их добавления
+        Expression::BinaryOp { left, op: op @ (BinaryOperator::Equal | BinaryOperator::NotEqual), right, .. }
+            if dict_types(left.get_type()).is_some() =>
+        {
+            let negation = if *op == BinaryOperator::NotEqual { "!" } else { "" };
+            format!(
+                "({}{{ let (lhs, rhs) = (&{}, &{}); lhs.1.len() == rhs.1.len() && \
+                 lhs.1.iter().all(|entry| rhs.0.get(&entry.0).is_some_and(|&position| rhs.1[position].1 == entry.1)) }})",
+                negation,
+                generate_place(left),
+                generate_place(right),
+            )
+        }
+        // Сложение строк: цепочка a + b + c собирается одним format!
+        Expression::BinaryOp { op: BinaryOperator::Add, expr_type, .. } if expr_type == "string" => {
+            let mut parts = Vec::new();
+            push_text_parts(expr, &mut parts);
+            format_macro("format", &"{}".repeat(parts.len()), parts)
+        }
+        // Сложение байтов: цепочка a + b + c собирается одним concat
+        Expression::BinaryOp { op: BinaryOperator::Add, .. } if is_bytes_type(expr.get_type()) => {
+            let mut parts = Vec::new();
+            push_byte_parts(expr, &mut parts);
+            format!("[{}].concat()", parts.join(", "))
+        }
+        // Повторение строки: отрицательное число повторений, как в Python, даёт пустую строку
+        Expression::BinaryOp { left, op: BinaryOperator::Multiply, right, expr_type, .. } if expr_type == "string" => {
+            let (text, count) = if is_text_type(left.get_type()) { (left, right) } else { (right, left) };
+            let is_unsigned = integer_range(count.get_type()).is_some_and(|(min, _)| min == 0);
+            let count = match constant_integer(count) {
+                Some(value) => value.max(0).to_string(),
+                None if is_unsigned => format!("{} as usize", generate_expression(count)),
+                None => format!("{}.max(0) as usize", generate_expression(count)),
+            };
+            format!("{}.repeat({})", generate_receiver(text), count)
+        }
+        // Объединение, пересечение, симметрическая разность и разность множеств
+        Expression::BinaryOp { left, op, right, expr_type, .. } if set_element_type(expr_type).is_some() => {
+            format!("(&{} {} &{})", generate_expression(left), rust_binary_operator(op), generate_expression(right))
+        }
+        // Сравнение двух констант: тип из контекста в Rust не выводится, нужны суффиксы
+        Expression::BinaryOp { left, op, right, .. } if op.is_comparison() && left.is_constant() && right.is_constant() => {
+            format!("({} {} {})", generate_typed_constant(left), rust_binary_operator(op), generate_typed_constant(right))
+        }
+        Expression::BinaryOp { left, op, right, expr_type, .. } => {
+            generate_binary_operation(*op, left, right, expr_type, generate_expression)
         }
         Expression::UnaryOp { op, expr, .. } => {
             let inner_expr = generate_expression(expr);
             let op_str = match op {
                 UnaryOperator::Negate => "-",
                 UnaryOperator::BitwiseNot => "!",
+                UnaryOperator::Not => "!",
             };
             format!("({}{})", op_str, inner_expr)
         }
-        Expression::CompoundAssign { name, op, value, .. } => {
-            let value_expr = generate_expression(value);
-            let op_str = match op {
-                BinaryOperator::Add => "+",
-                BinaryOperator::Subtract => "-",
-                BinaryOperator::Multiply => "*",
-                BinaryOperator::Divide => "/",
-                BinaryOperator::FloorDivide => "/",
-                BinaryOperator::Modulo => "%",
-                BinaryOperator::BitwiseOr => "|",
-                BinaryOperator::BitwiseAnd => "&",
-                BinaryOperator::BitwiseXor => "^",
+        Expression::Call { name, args, .. } => {
+            let args_str: Vec<String> = args.iter().map(generate_owned).collect();
+            format!("{}({})", name, args_str.join(", "))
+        }
+        Expression::Conversion { value, expr_type, .. } => generate_conversion(value, expr_type),
+        Expression::FormatString { parts, .. } => {
+            let mut format_str = String::new();
+            let mut format_args = Vec::new();
+            push_format_parts(parts, &mut format_str, &mut format_args);
+            // Без подстановок все скобки в строке формата удвоены, и format! не нужен
+            if format_args.is_empty() {
+                format!("\"{}\".to_string()", format_str.replace("{{", "{").replace("}}", "}"))
+            } else {
+                format_macro("format", &format_str, format_args)
+            }
+        }
+        Expression::List { elements, expr_type, .. } => {
+            if !elements.is_empty() {
+                let elements_str: Vec<String> = elements.iter().map(generate_owned).collect();
+                return format!("vec![{}]", elements_str.join(", "));
+            }
+            // Тип элементов указывается явно: из сравнения с другим Vec он не выводится.
+            // У пустого списка без контекста тип элементов неизвестен, подойдёт любой
+            match list_element_type(expr_type).and_then(get_type_mapping) {
+                Some(element_type) => format!("Vec::<{}>::new()", element_type),
+                None => "Vec::<()>::new()".to_string(),
+            }
+        }
+        Expression::Set { elements, expr_type, .. } => {
+            if !elements.is_empty() {
+                let elements_str: Vec<String> = elements.iter().map(generate_owned).collect();
+                return format!("std::collections::BTreeSet::from([{}])", elements_str.join(", "));
+            }
+            match set_element_type(expr_type).and_then(get_type_mapping) {
+                Some(element_type) => format!("std::collections::BTreeSet::<{}>::new()", element_type),
+                None => "std::collections::BTreeSet::<()>::new()".to_string(),
+            }
+        }
+        Expression::Tuple { elements, .. } => {
+            let elements_str: Vec<String> = elements.iter().map(generate_owned).collect();
+            match elements_str.as_slice() {
+                [element] => format!("({},)", element),
+                _ => format!("({})", elements_str.join(", ")),
+            }
+        }
+        // Пары добавляются по одной: повторный ключ, как в Python, заменяет значение
+        Expression::Dict { entries, expr_type, .. } => {
+            if !entries.is_empty() {
+                let entries_str: Vec<String> = entries
+                    .iter()
+                    .map(|(key, value)| format!("({}, {})", generate_owned(key), generate_owned(value)))
+                    .collect();
+                return format!(
+                    "{{ let mut dict: {} = Default::default(); for (key, value) in [{}] {{ {} }} dict }}",
+                    rust_type(expr_type),
+                    entries_str.join(", "),
+                    dict_insert("dict", "key", "value", expr_type),
+                );
+            }
+            match get_type_mapping(expr_type) {
+                Some(rust_type) => format!("<{}>::default()", rust_type),
+                None => "<(std::collections::HashMap<(), usize>, Vec<((), ())>)>::default()".to_string(),
+            }
+        }
+        // Элемент кортежа: индекс - неотрицательная константа, проверенная при разборе
+        Expression::Index { target, index, expr_type, .. } if tuple_element_types(target.get_type()).is_some() => {
+            let clone = if is_copy_type(expr_type) { "" } else { ".clone()" };
+            let tuple = if target.root_variable().is_some() {
+                generate_place(target)
+            } else {
+                format!("({})", generate_expression(target))
             };
-            format!("{} {} {}", name, op_str, value_expr)
+            format!("{}.{}{}", tuple, generate_expression(index), clone)
+        }
+        // Значение по ключу; отсутствующий ключ, как KeyError в Python, завершает программу
+        Expression::Index { target, index, expr_type, .. } if dict_types(target.get_type()).is_some() => {
+            let clone = if is_copy_type(expr_type) { "" } else { ".clone()" };
+            if target.root_variable().is_some() {
+                let dict = generate_place(target);
+                format!("{{ let key = {}; {}.1{} }}", generate_owned(index), dict_lookup(&dict, "key"), clone)
+            } else {
+                format!(
+                    "{{ let dict = {}; let key = {}; {}.1{} }}",
+                    generate_expression(target),
+                    generate_owned(index),
+                    dict_lookup("dict", "key"),
+                    clone,
+                )
+            }
+        }
+        // Символ строки по индексу Python
+        Expression::Index { target, index, .. } if is_text_type(target.get_type()) => format!(
+            "{{ let chars: Vec<char> = {}.chars().collect(); chars[{}] }}",
+            generate_receiver(target),
+            generate_position("chars", index),
+        ),
+        Expression::Index { target, index, expr_type, .. } => {
+            let clone = if is_copy_type(expr_type) { "" } else { ".clone()" };
+            if target.root_variable().is_some() {
+                let list = generate_place(target);
+                format!("{}[{}]{}", list, generate_position(&list, index), clone)
+            } else {
+                // Список, не хранящийся в переменной, вычисляется один раз
+                format!("{{ let list = {}; list[{}]{} }}", generate_expression(target), generate_position("list", index), clone)
+            }
+        }
+        Expression::Slice { target, start, stop, .. } => {
+            // Строка срезается по символам, а не по байтам
+            let is_text = is_text_type(target.get_type());
+            if start.is_none() && stop.is_none() {
+                let copy = if is_text { ".to_string()" } else { ".to_vec()" };
+                return format!("{}{}", generate_receiver(target), copy);
+            }
+            let (list, slice) = match is_text {
+                true => (format!("{}.chars().collect::<Vec<char>>()", generate_receiver(target)), "list[{}].iter().collect::<String>()"),
+                false => (format!("&{}", generate_place(target)), "list[{}].to_vec()"),
+            };
+            // Как в Python, границы за пределами списка обрезаются, а срез с началом
+            // после конца пуст
+            let bound = |bound: &Option<Box<Expression>>, default: &str| match bound {
+                Some(expr) => format!("{} as isize", generate_expression(expr)),
+                None => default.to_string(),
+            };
+            format!(
+                "{{ let list = {}; let length = list.len() as isize; \
+                 let bound = |index: isize| (if index < 0 {{ index + length }} else {{ index }}).clamp(0, length) as usize; \
+                 let start = bound({}); {} }}",
+                list,
+                bound(start, "0"),
+                slice.replace("{}", &format!("start..bound({}).max(start)", bound(stop, "length"))),
+            )
+        }
+        Expression::MethodCall { object, method, args, .. } => generate_method_call(object, method, args),
+        Expression::BuiltinCall { name, args, .. } => match name.as_str() {
+            "len" if is_text_type(args[0].get_type()) => format!("({}.chars().count() as i32)", generate_receiver(&args[0])),
+            "len" if dict_types(args[0].get_type()).is_some() => format!("({}.1.len() as i32)", generate_place(&args[0])),
+            "len" => format!("({}.len() as i32)", generate_place(&args[0])),
+            "set" => format!("{}.iter().cloned().collect::<std::collections::BTreeSet<_>>()", generate_place(&args[0])),
+            "bytes" | "bytearray" | "fromhex" => generate_bytes_call(name, args),
+            _ => {
+                let args_str: Vec<String> = args.iter().map(generate_expression).collect();
+                format!("{}({})", name, args_str.join(", "))
+            }
+        },
+        Expression::Bound { name, value, body, .. } => {
+            format!("{{ let {} = {}; {} }}", name, generate_expression(value), generate_expression(body))
         }
     }
 }
 
-// Функция для генерации Rust кода из распарсенной строки
-pub fn generate_rust_line(parsed: &ParsedLine) -> String {
-    match parsed {
-        ParsedLine::Print { content, comment, indent } => {
-            let indent_str = " ".repeat(*indent);
-            let mut line = format!("{}println!(\"{}\");", indent_str, content);
-            if let Some(comment_text) = comment {
-                if comment_text.is_empty() {
-                    line.push_str(" //");
+// Значение, которое сохраняется или передаётся дальше: переменная без Copy
+// копируется, чтобы оставаться доступной
+fn generate_owned(expr: &Expression) -> String {
+    match expr {
+        Expression::Variable { name, expr_type, .. } if !is_copy_type(expr_type) => format!("{}.clone()", name),
+        _ => generate_expression(expr),
+    }
+}
+
+// Обращение к переменной, элементу списка или значению словаря без копирования (для чтения на месте)
+fn generate_place(expr: &Expression) -> String {
+    match expr {
+        Expression::Literal { value, expr_type, .. } if expr_type == "bytes" => value.clone(),
+        Expression::Index { target, .. } if is_text_type(target.get_type()) => generate_expression(expr),
+        Expression::Index { target, index, .. } if expr.root_variable().is_some() && tuple_element_types(target.get_type()).is_some() => {
+            format!("{}.{}", generate_place(target), generate_expression(index))
+        }
+        Expression::Index { target, index, .. } if expr.root_variable().is_some() && dict_types(target.get_type()).is_some() => {
+            format!("{{ let key = {}; &{}.1 }}", generate_owned(index), dict_lookup(&generate_place(target), "key"))
+        }
+        Expression::Index { target, index, .. } if expr.root_variable().is_some() => {
+            let list = generate_place(target);
+            format!("{}[{}]", list, generate_position(&list, index))
+        }
+        _ => generate_expression(expr),
+    }
+}
+
+// Получатель вызова метода: код, который нельзя продолжить через точку, берётся в скобки
+fn generate_receiver(expr: &Expression) -> String {
+    match expr {
+        Expression::UnaryOp { .. } | Expression::Slice { .. } => format!("({})", generate_expression(expr)),
+        Expression::BinaryOp { expr_type, .. } if expr_type != "string" => format!("({})", generate_expression(expr)),
+        Expression::Index { .. } if expr.root_variable().is_none() => format!("({})", generate_expression(expr)),
+        _ => generate_place(expr),
+    }
+}
+
+// Строка для чтения без копирования (&str): литерал и значение str подставляются
+// без to_string, у значения string берётся as_str()
+fn generate_str(expr: &Expression) -> String {
+    match expr {
+        Expression::Conversion { value, .. } if is_text_type(value.get_type()) => generate_str(value),
+        _ if expr.get_type() == "str" => generate_expression(expr),
+        _ => format!("{}.as_str()", generate_receiver(expr)),
+    }
+}
+
+// Слагаемые сложения строк в порядке записи
+fn push_text_parts(expr: &Expression, parts: &mut Vec<String>) {
+    match expr {
+        Expression::BinaryOp { left, op: BinaryOperator::Add, right, expr_type, .. } if expr_type == "string" => {
+            push_text_parts(left, parts);
+            push_text_parts(right, parts);
+        }
+        Expression::Literal { .. } | Expression::Conversion { .. } => parts.push(generate_str(expr)),
+        _ => parts.push(generate_place(expr)),
+    }
+}
+
+// Слагаемые сложения байтов в порядке записи, каждое - срез &[u8]
+fn push_byte_parts(expr: &Expression, parts: &mut Vec<String>) {
+    match expr {
+        Expression::BinaryOp { left, op: BinaryOperator::Add, right, expr_type, .. } if is_bytes_type(expr_type) => {
+            push_byte_parts(left, parts);
+            push_byte_parts(right, parts);
+        }
+        Expression::Conversion { value, .. } if is_bytes_type(value.get_type()) => push_byte_parts(value, parts),
+        _ => parts.push(format!("&{}[..]", generate_receiver(expr))),
+    }
+}
+
+// Встроенные функции bytes, bytearray и bytes.fromhex: результат - Vec<u8>
+fn generate_bytes_call(name: &str, args: &[Expression]) -> String {
+    match (name, args) {
+        ("fromhex", [text]) => format!(
+            "{{ let digits: Vec<u8> = {}.bytes().filter(|byte| !byte.is_ascii_whitespace()).collect(); \
+             digits.chunks(2).map(|pair| std::str::from_utf8(pair).ok().filter(|_| pair.len() == 2)\
+             .and_then(|pair| u8::from_str_radix(pair, 16).ok()).expect(\"Некорректная шестнадцатеричная запись в fromhex\"))\
+             .collect::<Vec<u8>>() }}",
+            generate_receiver(text),
+        ),
+        (_, []) => "Vec::<u8>::new()".to_string(),
+        (_, [source]) if is_bytes_type(source.get_type()) => generate_owned(source),
+        (_, [source]) if source.get_type() == "list[uint8]" => generate_owned(source),
+        // Как в Python, значение вне диапазона байта или отрицательный размер завершают программу
+        (_, [source]) if list_element_type(source.get_type()).is_some() => format!(
+            "{}.iter().map(|&value| <u8 as std::convert::TryFrom<_>>::try_from(value).expect(\"Байт должен быть в диапазоне от 0 до 255\")).collect::<Vec<u8>>()",
+            generate_receiver(source),
+        ),
+        (_, [size]) => {
+            let is_unsigned = integer_range(size.get_type()).is_some_and(|(min, _)| min == 0);
+            let size = match constant_integer(size) {
+                Some(value) => value.to_string(),
+                None if is_unsigned => format!("{} as usize", generate_expression(size)),
+                None => format!("<usize as std::convert::TryFrom<_>>::try_from({}).expect(\"Отрицательный размер байтов\")", generate_expression(size)),
+            };
+            format!("vec![0u8; {}]", size)
+        }
+        _ => String::new(),
+    }
+}
+
+// Изменяемое обращение к переменной, элементу списка или значению словаря. Список нельзя
+// читать внутри изменяющего его обращения a[...], поэтому индексы, зависящие от длины списка,
+// вычисляются заранее, а значение словаря - через найденную заранее пару:
+// их объявления добавляются в lets
+fn generate_mutable_place(expr: &Expression, lets: &mut Vec<String>) -> String {
+    match expr {
+        Expression::Index { target, index, .. } if expr.root_variable().is_some() && tuple_element_types(target.get_type()).is_some() => {
+            format!("{}.{}", generate_mutable_place(target, lets), generate_expression(index))
+        }
+        Expression::Index { target, index, .. } if expr.root_variable().is_some() && dict_types(target.get_type()).is_some() => {
+            let dict = generate_mutable_place(target, lets);
+            let key = push_let(lets, "key", generate_owned(index));
+            let position = push_let(lets, "position", dict_position(&dict, &key));
+            format!("{}.1[{}].1", dict, position)
+        }
+        Expression::Index { target, index, .. } if expr.root_variable().is_some() => {
+            let list = generate_mutable_place(target, lets);
+            let position = generate_position(&list, index);
+            let is_unsigned = integer_range(index.get_type()).is_some_and(|(min, _)| min == 0);
+            if is_unsigned || constant_integer(index).is_some_and(|value| value >= 0) {
+                return format!("{}[{}]", list, position);
+            }
+            let name = push_let(lets, "index", position);
+            format!("{}[{}]", list, name)
+        }
+        _ => generate_expression(expr),
+    }
+}
+
+// Объявление заранее вычисляемого значения в lets под свободным именем: base, base2, base3, ...
+fn push_let(lets: &mut Vec<String>, base: &str, value: String) -> String {
+    let name = (1..)
+        .map(|n| if n == 1 { base.to_string() } else { format!("{}{}", base, n) })
+        .find(|name| !lets.iter().any(|declared| declared.starts_with(&format!("let {} =", name))))
+        .unwrap_or_default();
+    lets.push(format!("let {} = {};", name, value));
+    name
+}
+
+// Позиция пары словаря dict по ключу key; отсутствующий ключ завершает программу
+fn dict_position(dict: &str, key: &str) -> String {
+    format!("*{}.0.get(&{}).unwrap_or_else(|| panic!(\"Ключ {{:?}} не найден в словаре\", {}))", dict, key, key)
+}
+
+// Пара словаря dict по ключу key (ссылка на неё)
+fn dict_lookup(dict: &str, key: &str) -> String {
+    format!("{{ let dict = &{}; &dict.1[{}] }}", dict, dict_position("dict", key))
+}
+
+// Проходит ли обращение через значение словаря: d[k], d[k][i]
+fn writes_through_dict(expr: &Expression) -> bool {
+    match expr {
+        Expression::Index { target, .. } => dict_types(target.get_type()).is_some() || writes_through_dict(target),
+        _ => false,
+    }
+}
+
+// Код с заранее вычисленными индексами: { let index = ...; код }
+fn with_lets(lets: Vec<String>, code: String) -> String {
+    if lets.is_empty() {
+        code
+    } else {
+        format!("{{ {} {} }}", lets.join(" "), code)
+    }
+}
+
+// Позиция элемента в списке list по индексу Python: отрицательный индекс отсчитывается с конца
+fn generate_position(list: &str, index: &Expression) -> String {
+    let is_unsigned = integer_range(index.get_type()).is_some_and(|(min, _)| min == 0);
+    match constant_integer(index) {
+        Some(value) if value >= 0 => value.to_string(),
+        Some(value) => format!("{}.len() - {}", list, -value),
+        None if is_unsigned => format!("{} as usize", generate_expression(index)),
+        None => format!(
+            "{{ let index = {} as isize; if index < 0 {{ ({}.len() as isize + index) as usize }} else {{ index as usize }} }}",
+            generate_expression(index),
+            list,
+        ),
+    }
+}
+
+// Вызов метода словаря: get, keys, values, items
+fn generate_dict_method_call(object: &Expression, method: &str, args: &[Expression]) -> String {
+    let (key_type, value_type) = dict_types(object.get_type()).unwrap_or_default();
+    let dict = generate_place(object);
+    let clone = |type_name: &str| if is_copy_type(type_name) { "" } else { ".clone()" };
+    match (method, args) {
+        ("get", [key, default]) => format!(
+            "{{ let key = {}; let dict = &{}; dict.0.get(&key).map_or({}, |&position| dict.1[position].1{}) }}",
+            generate_owned(key),
+            dict,
+            generate_owned(default),
+            clone(&value_type),
+        ),
+        ("keys", []) => format!("{}.1.iter().map(|entry| entry.0{}).collect::<Vec<_>>()", dict, clone(&key_type)),
+        ("values", []) => format!("{}.1.iter().map(|entry| entry.1{}).collect::<Vec<_>>()", dict, clone(&value_type)),
+        // Пары словаря уже хранятся списком
+        ("items", []) => format!("{}.1.clone()", dict),
+        _ => {
+            let args_str: Vec<String> = args.iter().map(generate_owned).collect();
+            format!("{}.{}({})", dict, method, args_str.join(", "))
+        }
+    }
+}
+
+// Удаление элемента списка или пары словаря: del a[i], del d[k]
+fn generate_delete(target: &Expression) -> String {
+    let Expression::Index { target: collection, index, .. } = target else {
+        return String::new();
+    };
+    let mut lets = Vec::new();
+    let place = generate_mutable_place(collection, &mut lets);
+    if dict_types(collection.get_type()).is_none() {
+        return with_lets(lets, format!("{}.remove({});", place, generate_position(&place, index)));
+    }
+    // Пары после удалённой сдвигаются, как при удалении из списка, и их позиции уменьшаются
+    let key = push_let(&mut lets, "key", generate_owned(index));
+    let position = push_let(
+        &mut lets,
+        "position",
+        format!("{}.0.remove(&{}).unwrap_or_else(|| panic!(\"Ключ {{:?}} не найден в словаре\", {}))", place, key, key),
+    );
+    with_lets(
+        lets,
+        format!(
+            "{place}.1.remove({position}); for index in {place}.0.values_mut() {{ if *index > {position} {{ *index -= 1; }} }}",
+        ),
+    )
+}
+
+// Запись в словарь d[k] = v: замена значения существующего ключа или добавление пары
+fn generate_dict_insert(dict: &Expression, key: &Expression, value: &str, lets: &mut Vec<String>) -> String {
+    let dict_type = dict.get_type();
+    let dict = generate_mutable_place(dict, lets);
+    let key = push_let(lets, "key", generate_owned(key));
+    dict_insert(&dict, &key, value, dict_type)
+}
+
+// Замена значения ключа key в словаре dict типа dict_type или добавление пары в конец;
+// ключ хранится и в индексе, и в паре, поэтому ключ без Copy копируется
+fn dict_insert(dict: &str, key: &str, value: &str, dict_type: &str) -> String {
+    let (key_type, _) = dict_types(dict_type).unwrap_or_default();
+    let clone = if is_copy_type(&key_type) { "" } else { ".clone()" };
+    format!(
+        "if let Some(&position) = {dict}.0.get(&{key}) {{ {dict}.1[position].1 = {value}; }} \
+         else {{ {dict}.0.insert({key}{clone}, {dict}.1.len()); {dict}.1.push(({key}, {value})); }}",
+    )
+}
+
+// Вызов метода строки: результат - новая строка или значение, сама строка не меняется
+fn generate_text_method_call(object: &Expression, method: &str, args: &[Expression]) -> String {
+    let text = generate_receiver(object);
+    match (method, args) {
+        ("upper", []) => format!("{}.to_uppercase()", text),
+        ("lower", []) => format!("{}.to_lowercase()", text),
+        ("strip", []) => format!("{}.trim().to_string()", text),
+        ("split", []) => format!("{}.split_whitespace().map(str::to_string).collect::<Vec<String>>()", text),
+        ("split", [separator]) => {
+            format!("{}.split({}).map(str::to_string).collect::<Vec<String>>()", text, generate_str(separator))
+        }
+        ("join", [parts]) => format!("{}.join({})", generate_receiver(parts), generate_str(object)),
+        ("replace", [old, new]) => format!("{}.replace({}, {})", text, generate_str(old), generate_str(new)),
+        ("startswith", [prefix]) => format!("{}.starts_with({})", text, generate_str(prefix)),
+        ("endswith", [suffix]) => format!("{}.ends_with({})", text, generate_str(suffix)),
+        // Позиция в символах, а не в байтах; -1, если подстрока не найдена
+        ("find", [part]) => format!(
+            "{{ let text = {}; text.find({}).map_or(-1, |position| text[..position].chars().count() as i32) }}",
+            generate_str(object),
+            generate_str(part),
+        ),
+        // Строка уже хранит байты UTF-8, они копируются
+        ("encode", _) => format!("{}.as_bytes().to_vec()", text),
+        _ => {
+            let args_str: Vec<String> = args.iter().map(generate_str).collect();
+            format!("{}.{}({})", text, method, args_str.join(", "))
+        }
+    }
+}
+
+// Вызов метода bytes или bytearray; остальные методы bytearray - как у списка
+fn generate_bytes_method_call(object: &Expression, method: &str, args: &[Expression]) -> Option<String> {
+    let call = match (method, args) {
+        ("hex", []) => format!("{}.iter().map(|byte| format!(\"{{:02x}}\", byte)).collect::<String>()", generate_receiver(object)),
+        ("decode", _) => format!(
+            "std::str::from_utf8(&{}[..]).expect(\"Некорректная последовательность UTF-8\").to_string()",
+            generate_receiver(object),
+        ),
+        ("extend", [source]) => {
+            let mut lets = Vec::new();
+            let bytes = generate_mutable_place(object, &mut lets);
+            // Байты, дописываемые к самим себе, копируются
+            let source = match source {
+                Expression::Variable { name, .. } if *name == bytes => format!("&{}.clone()", name),
+                _ => format!("&{}[..]", generate_receiver(source)),
+            };
+            with_lets(lets, format!("{}.extend_from_slice({})", bytes, source))
+        }
+        _ => return None,
+    };
+    Some(call)
+}
+
+// Вызов метода списка или множества с поведением Python
+fn generate_method_call(object: &Expression, method: &str, args: &[Expression]) -> String {
+    if dict_types(object.get_type()).is_some() {
+        return generate_dict_method_call(object, method, args);
+    }
+    if is_text_type(object.get_type()) {
+        return generate_text_method_call(object, method, args);
+    }
+    if let Some(call) = is_bytes_type(object.get_type()).then(|| generate_bytes_method_call(object, method, args)).flatten() {
+        return call;
+    }
+    let mut lets = Vec::new();
+    let list = generate_mutable_place(object, &mut lets);
+    let call = match (method, args) {
+        ("add", [value]) => format!("{}.insert({})", list, generate_owned(value)),
+        // Отсутствующий элемент, в отличие от remove, не считается ошибкой
+        ("discard", [value]) => format!("{}.remove(&{})", list, generate_expression(value)),
+        ("append", [value]) => format!("{}.push({})", list, generate_owned(value)),
+        ("pop", []) => format!("{}.pop().expect(\"pop из пустого списка\")", list),
+        ("pop", [index]) => format!("{}.remove({})", list, generate_position(&list, index)),
+        // Индекс за пределами списка означает вставку в начало или в конец
+        ("insert", [index, value]) => {
+            let position = match constant_integer(index) {
+                Some(0) => "0".to_string(),
+                _ => format!(
+                    "{{ let length = {}.len() as isize; let index = {} as isize; \
+                     (if index < 0 {{ index + length }} else {{ index }}).clamp(0, length) as usize }}",
+                    list,
+                    generate_expression(index),
+                ),
+            };
+            format!("{}.insert({}, {})", list, position, generate_owned(value))
+        }
+        ("remove", [value]) => format!(
+            "{{ let value = {}; let position = {}.iter().position(|item| *item == value)\
+             .expect(\"remove: значение не найдено в списке\"); {}.remove(position); }}",
+            generate_owned(value),
+            list,
+            list,
+        ),
+        _ => {
+            let args_str: Vec<String> = args.iter().map(generate_owned).collect();
+            format!("{}.{}({})", list, method, args_str.join(", "))
+        }
+    };
+    with_lets(lets, call)
+}
+
+// Запись значения в том виде, в каком Python выводит элементы списка и словаря:
+// строки в кавычках, True/False, 1.0; результат - выражение типа String
+fn python_repr(value: &str, type_name: &str) -> String {
+    // Кортеж из одного элемента Python выводит с запятой: (1,)
+    if let Some(element_types) = tuple_element_types(type_name) {
+        let fields: Vec<String> = element_types
+            .iter()
+            .enumerate()
+            .map(|(i, element_type)| python_repr(&format!("tuple.{}", i), element_type))
+            .collect();
+        let format_str = match fields.len() {
+            1 => "({},)".to_string(),
+            n => format!("({})", vec!["{}"; n].join(", ")),
+        };
+        return format!("{{ let tuple = &{}; format!(\"{}\", {}) }}", value, format_str, fields.join(", "));
+    }
+    // Пустое множество Python выводит как set()
+    if let Some(element_type) = set_element_type(type_name) {
+        let item = if element_type == "bool" { "*item" } else { "item" };
+        return format!(
+            "{{ let set = &{}; if set.is_empty() {{ \"set()\".to_string() }} else {{ \
+             format!(\"{{{{{{}}}}}}\", set.iter().map(|item| {}).collect::<Vec<String>>().join(\", \")) }} }}",
+            value,
+            python_repr(item, element_type),
+        );
+    }
+    if let Some((key_type, value_type)) = dict_types(type_name) {
+        return format!(
+            "format!(\"{{{{{{}}}}}}\", {}.1.iter().map(|entry| format!(\"{{}}: {{}}\", {}, {})).collect::<Vec<String>>().join(\", \"))",
+            value,
+            python_repr("entry.0", &key_type),
+            python_repr("entry.1", &value_type),
+        );
+    }
+    if let Some(element_type) = list_element_type(type_name) {
+        let item = if element_type == "bool" { "*item" } else { "item" };
+        return format!(
+            "format!(\"[{{}}]\", {}.iter().map(|item| {}).collect::<Vec<String>>().join(\", \"))",
+            value,
+            python_repr(item, element_type),
+        );
+    }
+    match type_name {
+        "bool" => format!("(if {} {{ \"True\" }} else {{ \"False\" }}).to_string()", value),
+        "float" | "double" => python_float(value),
+        "str" | "string" | "char" => format!("format!(\"'{{}}'\", {})", value),
+        "bytes" | "bytearray" => python_bytes(value, type_name),
+        // Элементов у пустого списка без типа нет
+        "?" => "String::new()".to_string(),
+        _ => format!("{}.to_string()", value),
+    }
+}
+
+// Запись вещественного числа, как в Python: {:?} сохраняет дробную часть (1.0, а не 1),
+// а у экспоненты добавляются знак и ведущий ноль: 1e+20, 1e-05; nan - строчными буквами
+fn python_float(value: &str) -> String {
+    format!(
+        "{{ let text = format!(\"{{:?}}\", {}); match text.split_once('e') {{ \
+         Some((mantissa, exponent)) => {{ \
+         let (sign, digits) = exponent.strip_prefix('-').map_or((\"+\", exponent), |digits| (\"-\", digits)); \
+         format!(\"{{}}e{{}}{{:0>2}}\", mantissa, sign, digits) }} \
+         None => text.to_lowercase() }} }}",
+        value
+    )
+}
+
+// Запись байтов, как в Python: в апострофах, а если внутри есть апостроф и нет кавычки -
+// в кавычках; escape_ascii экранирует оба символа, лишнее экранирование снимается
+// (bytearray, как и Python, оставляет апостроф экранированным)
+fn python_bytes(value: &str, type_name: &str) -> String {
+    let (prefix, suffix, apostrophe) = if type_name == "bytearray" { ("bytearray(", ")", r"\\'") } else { ("", "", "'") };
+    format!(
+        r#"{{ let bytes = &{}[..]; let quote = if bytes.contains(&b'\'') && !bytes.contains(&b'"') {{ '"' }} else {{ '\'' }}; let text = bytes.escape_ascii().to_string(); let text = if quote == '"' {{ text.replace("\\'", "{}") }} else {{ text.replace("\\\"", "\"") }}; format!("{}b{{}}{{}}{{}}{}", quote, text, quote) }}"#,
+        value, apostrophe, prefix, suffix
+    )
+}
+
+// Знаковое целое в системе счисления x, X, o или b: Rust выводит отрицательное число
+// в дополнительном коде (ffffffd6), а Python - знак и модуль (-0x2a), поэтому модуль
+// форматируется отдельно; нули дополняют число после знака, остальное выравнивание
+// применяется к готовой записи. Тип значения указывается явно: у литерала он не известен
+fn signed_radix(value: &str, rust_type: &str, spec: &str) -> String {
+    let chars: Vec<char> = spec.chars().collect();
+    let align_end = match chars.iter().position(|c| matches!(c, '<' | '>' | '^')) {
+        Some(pos) if pos <= 1 => pos + 1,
+        _ => 0,
+    };
+    let align: String = chars[..align_end].iter().collect();
+    let mut flags = &chars[align_end..chars.len() - 1];
+    let plus = flags.first() == Some(&'+');
+    if plus {
+        flags = &flags[1..];
+    }
+    let alternate = if flags.first() == Some(&'#') { "#" } else { "" };
+    if !alternate.is_empty() {
+        flags = &flags[1..];
+    }
+    let zero = flags.first() == Some(&'0');
+    let width: String = flags.iter().filter(|c| c.is_ascii_digit()).collect::<String>().trim_start_matches('0').to_string();
+    let radix = chars[chars.len() - 1];
+    
+    let positive = if plus { "+" } else { "" };
+    let sign = format!("let sign = if value < 0 {{ \"-\" }} else {{ \"{}\" }};", positive);
+    if zero && !width.is_empty() {
+        return format!(
+            "{{ let value: {} = {}; {} format!(\"{{}}{{:{}0width${}}}\", sign, value.unsigned_abs(), width = {}usize.saturating_sub(sign.len())) }}",
+            rust_type, value, sign, alternate, radix, width
+        );
+    }
+    let text = format!("format!(\"{{}}{{:{}{}}}\", sign, value.unsigned_abs())", alternate, radix);
+    if width.is_empty() {
+        return format!("{{ let value: {} = {}; {} {} }}", rust_type, value, sign, text);
+    }
+    // Строки Rust по умолчанию выравнивает влево, а числа Python - вправо
+    let align = if align.is_empty() { ">".to_string() } else { align };
+    format!("{{ let value: {} = {}; {} format!(\"{{:{}{}}}\", {}) }}", rust_type, value, sign, align, width, text)
+}
+
+// Вызов макроса форматирования: format!, print! или println!
+fn format_macro(macro_name: &str, format_str: &str, format_args: Vec<String>) -> String {
+    if format_str.is_empty() {
+        return format!("{}!()", macro_name);
+    }
+    let mut call = format!("{}!(\"{}\"", macro_name, format_str);
+    for arg in format_args {
+        call.push_str(", ");
+        call.push_str(&arg);
+    }
+    call.push(')');
+    call
+}
+
+// Добавление частей f-строки в строку формата
+fn push_format_parts(parts: &[FormatPart], format_str: &mut String, format_args: &mut Vec<String>) {
+    for part in parts {
+        match part {
+            FormatPart::Text(text) => {
+                for c in text.chars() {
+                    match c {
+                        '{' => format_str.push_str("{{"),
+                        '}' => format_str.push_str("}}"),
+                        c => format_str.push_str(&escape_string_for_rust(&c.to_string())),
+                    }
+                }
+            }
+            FormatPart::Value { expr, spec } => {
+                let (placeholder, arg) = format_argument(expr, spec);
+                format_str.push_str(&placeholder);
+                format_args.push(arg);
+            }
+        }
+    }
+}
+
+// Заполнитель и аргумент макроса форматирования для значения в том виде,
+// в каком его выводит Python (True/False, 1.0, b'...', [1, 2])
+fn format_argument(expr: &Expression, spec: &str) -> (String, String) {
+    // Макрос держит ссылки на аргументы до конца вызова: элемент списка копируется заранее,
+    // иначе следующий аргумент не сможет изменить список (print(a[0], a.pop()))
+    let value_expr = match expr {
+        Expression::Index { .. } if is_copy_type(expr.get_type()) => format!("{{ let value = {}; value }}", generate_expression(expr)),
+        _ => generate_expression(expr),
+    };
+    let placeholder = |suffix: &str| match (spec, suffix) {
+        ("", "") => "{}".to_string(),
+        _ => format!("{{:{}{}}}", spec, suffix),
+    };
+    match expr.get_type() {
+        "bool" => (placeholder(""), format!("if {} {{ \"True\" }} else {{ \"False\" }}", value_expr)),
+        "float" | "double" if spec.is_empty() => ("{}".to_string(), python_float(&value_expr)),
+        // {:?} сохраняет дробную часть: 1.0, а не 1
+        "float" | "double" => (placeholder("?"), value_expr),
+        "bytes" | "bytearray" => ("{}".to_string(), python_bytes(&value_expr, expr.get_type())),
+        signed if spec.ends_with(['x', 'X', 'o', 'b']) && integer_range(signed).is_some_and(|(min, _)| min > 0) => {
+            ("{}".to_string(), signed_radix(&value_expr, &rust_type(signed), spec))
+        }
+        "None" => ("{}".to_string(), format!("{{ {}; \"None\" }}", value_expr)),
+        collection
+            if list_element_type(collection).is_some()
+                || set_element_type(collection).is_some()
+                || tuple_element_types(collection).is_some()
+                || dict_types(collection).is_some() =>
+        {
+            ("{}".to_string(), python_repr(&value_expr, collection))
+        }
+        _ => (placeholder(""), value_expr),
+    }
+}
+
+// Нужна ли для // и % поправка к операторам Rust: они округляют частное к нулю
+// и сохраняют знак делимого, а в Pando, как в Python, частное округляется вниз,
+// а знак остатка совпадает со знаком делителя. Для беззнаковых типов результаты совпадают
+fn needs_floor_semantics(op: BinaryOperator, type_name: &str) -> bool {
+    let is_unsigned = integer_range(type_name).is_some_and(|(min, _)| min == 0);
+    matches!(op, BinaryOperator::FloorDivide | BinaryOperator::Modulo) && !is_unsigned
+}
+
+// Генерация бинарной операции; operand генерирует код операндов
+fn generate_binary_operation(
+    op: BinaryOperator,
+    left: &Expression,
+    right: &Expression,
+    type_name: &str,
+    operand: fn(&Expression) -> String,
+) -> String {
+    let left_expr = operand(left);
+    let right_expr = operand(right);
+    // Литерал справа неотрицателен: минус перед ним - отдельная унарная операция
+    let literal_right = matches!(right, Expression::Literal { .. });
+    
+    if op == BinaryOperator::Power {
+        let rust_type = rust_type(type_name);
+        if !is_integer_type(type_name) {
+            return format!("{}::powf({}, {})", rust_type, left_expr, right_expr);
+        }
+        // Показатель степени у pow имеет тип u32, литерал подходит без суффикса
+        let exponent = if literal_right {
+            generate_expression(right)
+        } else {
+            format!("<u32 as std::convert::TryFrom<_>>::try_from({}).expect(\"Отрицательный показатель степени\")", right_expr)
+        };
+        return format!("{}::pow({}, {})", rust_type, left_expr, exponent);
+    }
+    
+    if !needs_floor_semantics(op, type_name) {
+        return format!("({} {} {})", left_expr, rust_binary_operator(&op), right_expr);
+    }
+    // При положительном делителе // и % совпадают с div_euclid и rem_euclid; вызов через тип:
+    // у переменной цикла по range(10) тип не указан и метод вызвать нельзя
+    if literal_right {
+        let method = if op == BinaryOperator::FloorDivide { "div_euclid" } else { "rem_euclid" };
+        return format!("{}::{}({}, {})", rust_type(type_name), method, left_expr, right_expr);
+    }
+    
+    let zero = if is_integer_type(type_name) { "0" } else { "0.0" };
+    match op {
+        BinaryOperator::FloorDivide if is_integer_type(type_name) => format!(
+            "{{ let (lhs, rhs) = ({}, {}); let quotient = lhs / rhs; \
+             if lhs % rhs != 0 && (lhs < 0) != (rhs < 0) {{ quotient - 1 }} else {{ quotient }} }}",
+            left_expr, right_expr
+        ),
+        // Вызов через тип: у частного двух литералов тип неизвестен и метод floor вызвать нельзя
+        BinaryOperator::FloorDivide => format!("{}::floor({} / {})", rust_type(type_name), left_expr, right_expr),
+        _ => format!(
+            "{{ let (lhs, rhs) = ({}, {}); let remainder = lhs % rhs; \
+             if remainder != {zero} && (remainder < {zero}) != (rhs < {zero}) {{ remainder + rhs }} else {{ remainder }} }}",
+            left_expr, right_expr
+        ),
+    }
+}
+
+// Явное преобразование: строка разбирается через parse, сужение целых проверяется
+// через try_from, а вещественного в целое - сравнением с границами типа (при переполнении
+// программа завершается с ошибкой), остальное - через as
+fn generate_conversion(value: &Expression, target: &str) -> String {
+    // Строка str копируется в string, а string передаётся в параметр str по ссылке;
+    // bytes и bytearray хранятся одинаково, байты передаются как есть
+    match target {
+        "string" => return format!("{}.to_string()", generate_receiver(value)),
+        "bytearray" => return generate_owned(value),
+        "str" => return generate_str(value),
+        _ => {}
+    }
+    let rust_type = rust_type(target);
+    let source = value.get_type();
+    let value_expr = generate_expression(value);
+    
+    if matches!(source, "str" | "string") {
+        format!("{}.trim().parse::<{}>().expect(\"Некорректная запись числа для {}\")", value_expr, rust_type, target)
+    } else if is_integer_type(source) && is_integer_type(target) && !is_integer_widening(source, target) {
+        format!("<{} as std::convert::TryFrom<_>>::try_from({}).expect(\"Значение не помещается в тип {}\")", rust_type, value_expr, target)
+    } else if !is_integer_type(source) && is_integer_type(target) {
+        // Дробная часть отбрасывается, как в Python; as молча заменил бы NaN и значения
+        // вне диапазона на 0 или границу типа, поэтому они проверяются явно
+        format!(
+            "{{ let value = ({}) as f64; \
+             if value.trunc() >= {rust}::MIN as f64 && value.trunc() < {rust}::MAX as f64 + 1.0 {{ value as {rust} }} \
+             else {{ panic!(\"Значение {{:?}} не помещается в тип {}\", value) }} }}",
+            value_expr,
+            target,
+            rust = rust_type,
+        )
+    } else {
+        format!("({} as {})", value_expr, rust_type)
+    }
+}
+
+// Генерация константы с суффиксами типа у литералов (5i64, 1.5f32);
+// для int и double суффикс не нужен, это типы литералов Rust по умолчанию
+fn generate_typed_constant(expr: &Expression) -> String {
+    match expr {
+        Expression::Literal { value, expr_type, .. } if expr_type != "int" && expr_type != "double" => {
+            format!("{}{}", value, get_type_mapping(expr_type).unwrap_or_default())
+        }
+        Expression::BinaryOp { left, op, right, expr_type, .. } => {
+            generate_binary_operation(*op, left, right, expr_type, generate_typed_constant)
+        }
+        Expression::UnaryOp { op: UnaryOperator::Negate, expr, .. } => format!("(-{})", generate_typed_constant(expr)),
+        Expression::UnaryOp { expr, .. } => format!("(!{})", generate_typed_constant(expr)),
+        other => generate_expression(other),
+    }
+}
+
+// Генерация print: строковые литералы встраиваются в строку формата,
+// остальные значения подставляются через {} в том виде, в каком их выводит Python
+fn generate_print(args: &[Expression], sep: &Option<Expression>, end: &Option<Expression>) -> String {
+    let mut format_str = String::new();
+    let mut format_args = Vec::new();
+    
+    for (i, arg) in args.iter().enumerate() {
+        if i > 0 {
+            match sep {
+                Some(sep) => push_print_value(sep, &mut format_str, &mut format_args),
+                None => format_str.push(' '),
+            }
+        }
+        push_print_value(arg, &mut format_str, &mut format_args);
+    }
+    
+    // Перевод строки в конце - это println!
+    let macro_name = match end {
+        None => "println",
+        Some(Expression::Literal { value, expr_type, span }) if expr_type == "str" && ends_with_newline(value) => {
+            let rest = Expression::Literal {
+                value: format!("{}\"", &value[..value.len() - 3]),
+                expr_type: expr_type.clone(),
+                span: *span,
+            };
+            push_print_value(&rest, &mut format_str, &mut format_args);
+            "println"
+        }
+        Some(end) => {
+            push_print_value(end, &mut format_str, &mut format_args);
+            "print"
+        }
+    };
+    
+    format_macro(macro_name, &format_str, format_args)
+}
+
+// Заканчивается ли строковый литерал Rust переводом строки (\n, но не \\n)
+fn ends_with_newline(literal: &str) -> bool {
+    match literal.strip_suffix("\\n\"") {
+        Some(rest) => rest.chars().rev().take_while(|c| *c == '\\').count() % 2 == 0,
+        None => false,
+    }
+}
+
+// Добавление значения в вызов print: литералы и f-строки встраиваются в строку формата
+fn push_print_value(expr: &Expression, format_str: &mut String, format_args: &mut Vec<String>) {
+    match expr {
+        // Фигурные скобки из \u{...} нельзя удваивать, такие строки подставляются целиком
+        Expression::Literal { value, expr_type, .. } if expr_type == "str" && !value.contains("\\u{") => {
+            format_str.push_str(&value[1..value.len() - 1].replace('{', "{{").replace('}', "}}"));
+        }
+        Expression::Literal { expr_type, .. } if expr_type == "None" => format_str.push_str("None"),
+        Expression::FormatString { parts, .. } => push_format_parts(parts, format_str, format_args),
+        _ => {
+            let (placeholder, arg) = format_argument(expr, "");
+            format_str.push_str(&placeholder);
+            format_args.push(arg);
+        }
+    }
+}
+
+// Составное присваивание переменной или элементу списка. Для степени и // и % со знаком
+// нет подходящего оператора Rust вида op=: присваиваем результат обычной бинарной операции
+fn generate_compound_assign(place: &str, op: BinaryOperator, value: &Expression) -> String {
+    let var_type = value.get_type();
+    // Строка дополняется на месте; дописываемая переменная может совпадать с изменяемой,
+    // поэтому она копируется
+    if var_type == "string" {
+        let text = match value {
+            Expression::Variable { name, .. } if name == place => format!("&{}.clone()", name),
+            _ => generate_str(value),
+        };
+        return format!("{}.push_str({});", place, text);
+    }
+    // Байты дополняются на месте, как строка
+    if is_bytes_type(var_type) {
+        let bytes = match value {
+            Expression::Variable { name, .. } if name == place => format!("&{}.clone()", name),
+            Expression::Conversion { value, .. } => format!("&{}[..]", generate_receiver(value)),
+            _ => format!("&{}[..]", generate_receiver(value)),
+        };
+        return format!("{}.extend_from_slice({});", place, bytes);
+    }
+    // У множеств нет операторов с присваиванием: результат операции заменяет множество
+    if set_element_type(var_type).is_some() {
+        return format!("{} = &{} {} &{};", place, place, rust_binary_operator(&op), generate_expression(value));
+    }
+    if op == BinaryOperator::Power || needs_floor_semantics(op, var_type) {
+        // Текущее значение читается по тому же обращению, что и присваивается
+        let current = Expression::Variable {
+            name: place.to_string(),
+            expr_type: var_type.to_string(),
+            span: value.span(),
+        };
+        let operation = Expression::BinaryOp {
+            left: Box::new(current),
+            op,
+            right: Box::new(value.clone()),
+            expr_type: var_type.to_string(),
+            span: value.span(),
+        };
+        format!("{} = {};", place, generate_expression(&operation))
+    } else {
+        format!("{} {}= {};", place, rust_binary_operator(&op), generate_expression(value))
+    }
+}
+
+// Форматирование комментария в конце строки
+fn format_comment(comment: &Option<String>) -> String {
+    match comment {
+        Some(comment_text) if comment_text.is_empty() => " //".to_string(),
+        Some(comment_text) => format!(" // {}", comment_text),
+        None => String::new(),
+    }
+}
+
+// Генерация условия без лишних внешних скобок
+fn generate_condition(expr: &Expression) -> String {
+    let condition = generate_expression(expr);
+    match expr {
+        Expression::BinaryOp { .. } | Expression::UnaryOp { .. } => {
+            condition[1..condition.len() - 1].to_string()
+        }
+        _ => condition,
+    }
+}
+
+// Генерация источника значений для цикла for
+fn generate_for_iterable(iterable: &ForIterable) -> String {
+    match iterable {
+        ForIterable::Range { start, stop, step } => {
+            // Если обе границы константы, тип переменной цикла задаёт суффикс начала
+            let start_expr = if start.is_constant() && stop.is_constant() {
+                generate_typed_constant(start)
+            } else {
+                generate_expression(start)
+            };
+            let stop_expr = generate_expression(stop);
+            if *step > 0 {
+                let range = format!("{}..{}", start_expr, stop_expr);
+                if *step == 1 {
+                    range
                 } else {
-                    line.push_str(&format!(" // {}", comment_text));
+                    format!("({}).step_by({})", range, step)
+                }
+            } else {
+                // Отрицательный шаг: значения от start вниз до stop (не включая stop)
+                let range = format!("(({} + 1)..={}).rev()", stop_expr, start_expr);
+                if *step == -1 {
+                    range
+                } else {
+                    format!("{}.step_by({})", range, -step)
                 }
             }
-            line
         }
-        ParsedLine::VariableDecl { name, type_name, value, comment, indent } => {
-            let indent_str = " ".repeat(*indent);
-            let rust_type = get_type_mapping(type_name).unwrap_or("i32");
+        ForIterable::Chars(expr) => format!("{}.chars()", generate_expression(expr)),
+        ForIterable::Bytes(expr) => format!("{}.iter().copied()", generate_expression(expr)),
+        // Цикл идёт по копии списка или словаря: тело может изменять исходный
+        ForIterable::List(expr) => generate_owned(expr),
+        ForIterable::Keys(expr) => format!("{}.1.into_iter().map(|entry| entry.0)", generate_owned(expr)),
+    }
+}
+
+// Генерация цикла; при наличии ветки else цикл оборачивается в блок,
+// из которого break выходит целиком, пропуская else
+fn generate_loop(
+    keyword: &str,
+    body: &[ParsedLine],
+    else_branch: &Option<Block>,
+    loop_label: &Option<String>,
+    else_label: &Option<String>,
+    comment: &Option<String>,
+    depth: usize,
+) -> String {
+    let indent_str = "    ".repeat(depth);
+    let loop_label_str = loop_label.as_ref().map(|l| format!("{}: ", l)).unwrap_or_default();
+    let header = format!("{}{} {{{}\n", loop_label_str, keyword, format_comment(comment));
+    
+    match else_branch {
+        None => format!("{}{}{}{}}}", indent_str, header, generate_block(body, depth + 1), indent_str),
+        Some(block) => {
+            let inner_indent = "    ".repeat(depth + 1);
+            let else_label_str = else_label.as_ref().map(|l| format!("{}: ", l)).unwrap_or_default();
+            let else_comment = match &block.comment {
+                Some(text) if !text.is_empty() => format!("{}// {}\n", inner_indent, text),
+                _ => String::new(),
+            };
+            format!(
+                "{}{}{{\n{}{}{}{}}}\n{}{}{}}}",
+                indent_str,
+                else_label_str,
+                inner_indent,
+                header,
+                generate_block(body, depth + 2),
+                inner_indent,
+                else_comment,
+                generate_block(&block.body, depth + 1),
+                indent_str,
+            )
+        }
+    }
+}
+
+// Генерация всей программы: константы и функции верхнего уровня, затем main с остальными инструкциями
+pub fn generate_program(lines: &[ParsedLine]) -> String {
+    let (items, mut statements): (Vec<ParsedLine>, Vec<ParsedLine>) = lines
+        .iter()
+        .cloned()
+        .partition(|line| matches!(line, ParsedLine::Function { .. } | ParsedLine::Const { .. }));
+    let (constants, functions): (Vec<ParsedLine>, Vec<ParsedLine>) = items
+        .into_iter()
+        .partition(|line| matches!(line, ParsedLine::Const { .. }));
+    
+    // На месте вынесенных функций и констант остаются пустые строки: схлопываем их
+    statements.dedup_by(|a, b| matches!((a, b), (ParsedLine::Empty, ParsedLine::Empty)));
+    if matches!(statements.last(), Some(ParsedLine::Empty)) {
+        statements.pop();
+    }
+    if matches!(statements.first(), Some(ParsedLine::Empty)) {
+        statements.remove(0);
+    }
+    
+    let mut rust_code = String::new();
+    for constant in &constants {
+        rust_code.push_str(&generate_rust_line(constant, 0));
+        rust_code.push('\n');
+    }
+    if !constants.is_empty() {
+        rust_code.push('\n');
+    }
+    for function in &functions {
+        rust_code.push_str(&generate_rust_line(function, 0));
+        rust_code.push_str("\n\n");
+    }
+    
+    rust_code.push_str("fn main() {\n");
+    rust_code.push_str(&generate_block(&statements, 1));
+    rust_code.push('}');
+    rust_code
+}
+
+// Функция для генерации блока строк с заданной глубиной вложенности
+pub fn generate_block(lines: &[ParsedLine], depth: usize) -> String {
+    let mut code = String::new();
+    for parsed in lines {
+        // pass без комментария кода не порождает
+        if matches!(parsed, ParsedLine::Pass { comment: None, .. }) {
+            continue;
+        }
+        code.push_str(&generate_rust_line(parsed, depth));
+        code.push('\n');
+    }
+    code
+}
+
+// Функция для генерации Rust кода из распарсенной строки
+pub fn generate_rust_line(parsed: &ParsedLine, depth: usize) -> String {
+    let indent_str = "    ".repeat(depth);
+    match parsed {
+        ParsedLine::Print { args, sep, end, comment, .. } => {
+            format!("{}{};{}", indent_str, generate_print(args, sep, end), format_comment(comment))
+        }
+        ParsedLine::VariableDecl { name, type_name, value, mutable, comment, .. } => {
+            let rust_type = rust_type(type_name);
+            // Значение объявленной без него переменной присваивается позже (это проверяет анализ)
             let rust_value = match value {
-                Some(expr) => {
-                    let expr_str = generate_expression(expr);
-                    // Специальная обработка для bytearray с байтовыми строками
-                    if type_name == "bytearray" && expr_str.starts_with("b\"") {
-                        format!("{}.to_vec()", expr_str)
-                    } else {
-                        expr_str
-                    }
+                // Тип пустого списка или словаря уже указан в объявлении
+                Some(Expression::List { elements, .. }) if elements.is_empty() => " = Vec::new()".to_string(),
+                Some(Expression::Dict { entries, .. }) if entries.is_empty() => {
+                    " = (std::collections::HashMap::new(), Vec::new())".to_string()
+                }
+                Some(Expression::Set { elements, .. }) if elements.is_empty() => {
+                    " = std::collections::BTreeSet::new()".to_string()
                 }
-                None => get_default_value(type_name),
+                Some(expr) => format!(" = {}", generate_owned(expr)),
+                None => String::new(),
             };
             
-            // Все переменные теперь объявляются с mut
-            let mut line = format!("{}let mut {}: {} = {};", indent_str, name, rust_type, rust_value);
-            if let Some(comment_text) = comment {
-                if comment_text.is_empty() {
-                    line.push_str(" //");
-                } else {
-                    line.push_str(&format!(" // {}", comment_text));
+            // mut только у переменных, которые переприсваиваются
+            let binding = if *mutable { "let mut" } else { "let" };
+            format!("{}{} {}: {}{};{}", indent_str, binding, name, rust_type, rust_value, format_comment(comment))
+        }
+        ParsedLine::Const { name, type_name, value, comment, .. } => {
+            let rust_type = rust_type(type_name);
+            format!("{}const {}: {} = {};{}", indent_str, name, rust_type, generate_expression(value), format_comment(comment))
+        }
+        ParsedLine::VariableAssign { name, value, comment, .. } => {
+            let value_expr = generate_owned(value);
+            format!("{}{} = {};{}", indent_str, name, value_expr, format_comment(comment))
+        }
+        // Новые переменные объявляются вместе с распаковкой, если других целей нет,
+        // иначе - заранее, без значения
+        ParsedLine::TupleAssign { targets, declared, mutable, value, comment, .. } => {
+            let value_expr = generate_owned(value);
+            let binding = |name: &String, mutable: &bool| if *mutable { format!("mut {}", name) } else { name.clone() };
+            if declared.iter().zip(targets).all(|(declared_type, name)| declared_type.is_some() || name == "_") {
+                let patterns: Vec<String> = targets.iter().zip(mutable).map(|(name, mutable)| binding(name, mutable)).collect();
+                return format!(
+                    "{}let ({}): {} = {};{}",
+                    indent_str,
+                    patterns.join(", "),
+                    rust_type(value.get_type()),
+                    value_expr,
+                    format_comment(comment),
+                );
+            }
+            let mut code = String::new();
+            for ((name, declared_type), mutable) in targets.iter().zip(declared).zip(mutable) {
+                if let Some(declared_type) = declared_type {
+                    code.push_str(&format!("{}let {}: {};\n", indent_str, binding(name, mutable), rust_type(declared_type)));
                 }
             }
-            line
+            format!("{}{}({}) = {};{}", code, indent_str, targets.join(", "), value_expr, format_comment(comment))
         }
-        ParsedLine::VariableAssign { name, value, comment, indent } => {
-            let indent_str = " ".repeat(*indent);
-            let value_expr = generate_expression(value);
-            
-            // Определяем, является ли это составным присваиванием
-            let line = if let Expression::CompoundAssign { op, .. } = value {
-                let op_str = match op {
-                    BinaryOperator::Add => "+=",
-                    BinaryOperator::Subtract => "-=",
-                    BinaryOperator::Multiply => "*=",
-                    BinaryOperator::Divide => "/=",
-                    BinaryOperator::FloorDivide => "/=",
-                    BinaryOperator::Modulo => "%=",
-                    BinaryOperator::BitwiseOr => "|=",
-                    BinaryOperator::BitwiseAnd => "&=",
-                    BinaryOperator::BitwiseXor => "^=",
-                };
-                format!("{}{} {};", indent_str, name, op_str)
-            } else {
-                format!("{}{} = {};", indent_str, name, value_expr)
+        ParsedLine::CompoundAssign { name, op, value, comment, .. } => {
+            format!("{}{}{}", indent_str, generate_compound_assign(name, *op, value), format_comment(comment))
+        }
+        ParsedLine::ItemAssign { target, op, value, comment, .. } => {
+            let mut lets = Vec::new();
+            // Найденная пара словаря держит изменяемую ссылку на него до конца присваивания,
+            // поэтому значение, которое может читать тот же словарь, вычисляется заранее;
+            // так же вычисляются строка и байты, дописываемые к элементу: они могут читать тот же список
+            let is_literal = match value {
+                Expression::Conversion { value, .. } => matches!(**value, Expression::Literal { .. }),
+                _ => matches!(value, Expression::Literal { .. }),
             };
-            
-            if let Some(comment_text) = comment {
-                if comment_text.is_empty() {
-                    format!("{} //", line)
-                } else {
-                    format!("{} // {}", line, comment_text)
+            let appends = op.is_some() && matches!(value.get_type(), "string" | "bytearray") && !is_literal;
+            let hoisted = (writes_through_dict(target) || appends).then(|| Expression::Variable {
+                name: push_let(&mut lets, "new_value", generate_owned(value)),
+                expr_type: value.get_type().to_string(),
+                span: value.span(),
+            });
+            let value_str = match &hoisted {
+                Some(Expression::Variable { name, .. }) => name.clone(),
+                _ => generate_owned(value),
+            };
+            let statement = match (op, target) {
+                (None, Expression::Index { target: dict, index: key, .. }) if dict_types(dict.get_type()).is_some() => {
+                    generate_dict_insert(dict, key, &value_str, &mut lets)
                 }
+                (None, _) => format!("{} = {};", generate_mutable_place(target, &mut lets), value_str),
+                (Some(op), _) => {
+                    let place = generate_mutable_place(target, &mut lets);
+                    generate_compound_assign(&place, *op, hoisted.as_ref().unwrap_or(value))
+                }
+            };
+            format!("{}{}{}", indent_str, with_lets(lets, statement), format_comment(comment))
+        }
+        ParsedLine::Delete { target, comment, .. } => {
+            format!("{}{}{}", indent_str, generate_delete(target), format_comment(comment))
+        }
+        ParsedLine::If { branches, else_branch, .. } => {
+            let mut code = String::new();
+            for (i, branch) in branches.iter().enumerate() {
+                let keyword = if i == 0 {
+                    format!("{}if", indent_str)
+                } else {
+                    " else if".to_string()
+                };
+                code.push_str(&format!(
+                    "{} {} {{{}\n{}{}}}",
+                    keyword,
+                    generate_condition(&branch.condition),
+                    format_comment(&branch.comment),
+                    generate_block(&branch.body, depth + 1),
+                    indent_str,
+                ));
+            }
+            if let Some(block) = else_branch {
+                code.push_str(&format!(
+                    " else {{{}\n{}{}}}",
+                    format_comment(&block.comment),
+                    generate_block(&block.body, depth + 1),
+                    indent_str,
+                ));
+            }
+            code
+        }
+        ParsedLine::While { condition, body, else_branch, loop_label, else_label, comment, .. } => {
+            // while True превращается в loop, чтобы не получать предупреждение rustc
+            let keyword = match condition {
+                Expression::Literal { value, .. } if value == "true" => "loop".to_string(),
+                _ => format!("while {}", generate_condition(condition)),
+            };
+            generate_loop(&keyword, body, else_branch, loop_label, else_label, comment, depth)
+        }
+        ParsedLine::For { var_names, mutable, iterable, body, else_branch, loop_label, else_label, comment, .. } => {
+            let bindings: Vec<String> = var_names
+                .iter()
+                .zip(mutable)
+                .map(|(name, mutable)| if *mutable { format!("mut {}", name) } else { name.clone() })
+                .collect();
+            let binding = match bindings.as_slice() {
+                [binding] => binding.clone(),
+                _ => format!("({})", bindings.join(", ")),
+            };
+            let keyword = format!("for {} in {}", binding, generate_for_iterable(iterable));
+            generate_loop(&keyword, body, else_branch, loop_label, else_label, comment, depth)
+        }
+        ParsedLine::Function { name, params, mutable_params, return_type, body, comment, .. } => {
+            let params_str: Vec<String> = params.iter().map(|(param_name, param_type)| {
+                // Параметры, как и переменные, получают mut, только если переприсваиваются
+                let binding = if mutable_params.contains(param_name) { "mut " } else { "" };
+                format!("{}{}: {}", binding, param_name, signature_type(param_type))
+            }).collect();
+            let return_str = if return_type == "None" {
+                String::new()
             } else {
-                line
+                format!(" -> {}", signature_type(return_type))
+            };
+            let lifetime = if params_str.iter().any(|param| param.contains("&'a")) || return_str.contains("&'a") { "<'a>" } else { "" };
+            format!(
+                "{}fn {}{}({}){} {{{}\n{}{}}}",
+                indent_str,
+                name,
+                lifetime,
+                params_str.join(", "),
+                return_str,
+                format_comment(comment),
+                generate_block(body, depth + 1),
+                indent_str,
+            )
+        }
+        ParsedLine::Return { value, comment, .. } => {
+            match value {
+                Some(expr) => format!("{}return {};{}", indent_str, generate_expression(expr), format_comment(comment)),
+                None => format!("{}return;{}", indent_str, format_comment(comment)),
+            }
+        }
+        ParsedLine::ExpressionStatement { expr, comment, .. } => {
+            format!("{}{};{}", indent_str, generate_expression(expr), format_comment(comment))
+        }
+        ParsedLine::Break { label, comment, .. } => {
+            match label {
+                Some(l) => format!("{}break {};{}", indent_str, l, format_comment(comment)),
+                None => format!("{}break;{}", indent_str, format_comment(comment)),
+            }
+        }
+        ParsedLine::Continue { label, comment, .. } => {
+            match label {
+                Some(l) => format!("{}continue {};{}", indent_str, l, format_comment(comment)),
+                None => format!("{}continue;{}", indent_str, format_comment(comment)),
             }
         }
-        ParsedLine::Comment { content, indent } => {
-            let indent_str = " ".repeat(*indent);
+        ParsedLine::Pass { comment, .. } => format!("{}{}", indent_str, format_comment(comment).trim_start()),
+        ParsedLine::Comment { content } => {
             format!("{}{}", indent_str, content)
         }
         ParsedLine::Empty => "".to_string(),
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,429 @@
+use crate::error::TranspilerError;
+use crate::types::BinaryOperator;
+
+// Вид токена выражения
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    Integer(String),
+    Float(String),
+    Str(String),
+    Bytes(String),
+    // Содержимое f-строки в исходном виде: разбирается парсером выражений
+    FormatString(String),
+    Char(String),
+    Identifier(String),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    DoubleSlash,
+    Percent,
+    Pipe,
+    Ampersand,
+    Caret,
+    Tilde,
+    DoubleStar,
+    ShiftLeft,
+    ShiftRight,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Assign,
+    CompoundAssign(BinaryOperator),
+    LeftParen,
+    RightParen,
+    LeftBracket,
+    RightBracket,
+    LeftBrace,
+    RightBrace,
+    Comma,
+    Colon,
+    Dot,
+    Arrow,
+    Eof,
+}
+
+// Токен с позицией в исходной строке (в символах, конец не включается)
+#[derive(Debug, Clone)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub start: usize,
+    pub end: usize,
+}
+
+// Операторы из нескольких символов (проверяются раньше односимвольных)
+const MULTI_CHAR_OPERATORS: &[(&str, TokenKind)] = &[
+    ("//=", TokenKind::CompoundAssign(BinaryOperator::FloorDivide)),
+    ("**=", TokenKind::CompoundAssign(BinaryOperator::Power)),
+    ("<<=", TokenKind::CompoundAssign(BinaryOperator::ShiftLeft)),
+    (">>=", TokenKind::CompoundAssign(BinaryOperator::ShiftRight)),
+    ("//", TokenKind::DoubleSlash),
+    ("**", TokenKind::DoubleStar),
+    ("<<", TokenKind::ShiftLeft),
+    (">>", TokenKind::ShiftRight),
+    ("->", TokenKind::Arrow),
+    ("==", TokenKind::Equal),
+    ("!=", TokenKind::NotEqual),
+    ("<=", TokenKind::LessEqual),
+    (">=", TokenKind::GreaterEqual),
+    ("+=", TokenKind::CompoundAssign(BinaryOperator::Add)),
+    ("-=", TokenKind::CompoundAssign(BinaryOperator::Subtract)),
+    ("*=", TokenKind::CompoundAssign(BinaryOperator::Multiply)),
+    ("/=", TokenKind::CompoundAssign(BinaryOperator::Divide)),
+    ("%=", TokenKind::CompoundAssign(BinaryOperator::Modulo)),
+    ("|=", TokenKind::CompoundAssign(BinaryOperator::BitwiseOr)),
+    ("&=", TokenKind::CompoundAssign(BinaryOperator::BitwiseAnd)),
+    ("^=", TokenKind::CompoundAssign(BinaryOperator::BitwiseXor)),
+];
+
+// Оператор составного присваивания в начале текста и его длина в байтах
+pub fn compound_assign_operator(text: &str) -> Option<(BinaryOperator, usize)> {
+    MULTI_CHAR_OPERATORS.iter().find_map(|(op, kind)| match kind {
+        TokenKind::CompoundAssign(operator) if text.starts_with(op) => Some((*operator, op.len())),
+        _ => None,
+    })
+}
+
+// Разбиение выражения на токены
+pub fn tokenize(expr: &str, line_num: usize, column: usize) -> Result<Vec<Token>, TranspilerError> {
+    let chars: Vec<char> = expr.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        // Числа: 42, 1_000, 3.14, 1e-5, .5
+        if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).is_some_and(|d| d.is_ascii_digit())) {
+            let (kind, end) = read_number(&chars, i);
+            tokens.push(Token { kind, start, end });
+            i = end;
+            continue;
+        }
+
+        // Байтовая строка b"..."
+        if c == 'b' && chars.get(i + 1) == Some(&'"') {
+            let (content, end) = read_quoted(&chars, i + 1, '"', QuoteKind::Bytes, line_num, column)?;
+            tokens.push(Token { kind: TokenKind::Bytes(content), start, end });
+            i = end;
+            continue;
+        }
+
+        // f-строка f"..."
+        if c == 'f' && chars.get(i + 1) == Some(&'"') {
+            let (content, end) = read_quoted(&chars, i + 1, '"', QuoteKind::Format, line_num, column)?;
+            tokens.push(Token { kind: TokenKind::FormatString(content), start, end });
+            i = end;
+            continue;
+        }
+
+        if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let name: String = chars[start..i].iter().collect();
+            tokens.push(Token { kind: TokenKind::Identifier(name), start, end: i });
+            continue;
+        }
+
+        if c == '"' {
+            let (content, end) = read_quoted(&chars, i, '"', QuoteKind::Text, line_num, column)?;
+            tokens.push(Token { kind: TokenKind::Str(content), start, end });
+            i = end;
+            continue;
+        }
+
+        if c == '\'' {
+            let (content, end) = read_quoted(&chars, i, '\'', QuoteKind::Text, line_num, column)?;
+            tokens.push(Token { kind: TokenKind::Char(content), start, end });
+            i = end;
+            continue;
+        }
+
+        let rest: String = chars[i..chars.len().min(i + 3)].iter().collect();
+        if let Some((op, kind)) = MULTI_CHAR_OPERATORS.iter().find(|(op, _)| rest.starts_with(op)) {
+            let end = i + op.chars().count();
+            tokens.push(Token { kind: kind.clone(), start, end });
+            i = end;
+            continue;
+        }
+
+        let kind = match c {
+            '+' => TokenKind::Plus,
+            '-' => TokenKind::Minus,
+            '*' => TokenKind::Star,
+            '/' => TokenKind::Slash,
+            '%' => TokenKind::Percent,
+            '|' => TokenKind::Pipe,
+            '&' => TokenKind::Ampersand,
+            '^' => TokenKind::Caret,
+            '~' => TokenKind::Tilde,
+            '<' => TokenKind::Less,
+            '>' => TokenKind::Greater,
+            '=' => TokenKind::Assign,
+            '(' => TokenKind::LeftParen,
+            ')' => TokenKind::RightParen,
+            '[' => TokenKind::LeftBracket,
+            ']' => TokenKind::RightBracket,
+            '{' => TokenKind::LeftBrace,
+            '}' => TokenKind::RightBrace,
+            ',' => TokenKind::Comma,
+            ':' => TokenKind::Colon,
+            '.' => TokenKind::Dot,
+            _ => {
+                return Err(TranspilerError::new(
+                    &format!("Неожиданный символ '{}'", c),
+                    line_num,
+                    column + start,
+                )
+                .with_code("syntax"));
+            }
+        };
+        tokens.push(Token { kind, start, end: i + 1 });
+        i += 1;
+    }
+
+    tokens.push(Token { kind: TokenKind::Eof, start: chars.len(), end: chars.len() });
+    Ok(tokens)
+}
+
+// Чтение числового литерала; возвращает токен и позицию после него
+fn read_number(chars: &[char], start: usize) -> (TokenKind, usize) {
+    let is_digit = |i: usize| chars.get(i).is_some_and(|c| c.is_ascii_digit() || *c == '_');
+    let mut i = start;
+    let mut is_float = false;
+
+    while is_digit(i) {
+        i += 1;
+    }
+
+    if chars.get(i) == Some(&'.') && !chars.get(i + 1).is_some_and(|c| c.is_alphabetic() || *c == '_') {
+        is_float = true;
+        i += 1;
+        while is_digit(i) {
+            i += 1;
+        }
+    }
+
+    // Экспонента: e10, e-5, E+3
+    if matches!(chars.get(i), Some('e' | 'E')) {
+        let sign = usize::from(matches!(chars.get(i + 1), Some('+' | '-')));
+        if chars.get(i + 1 + sign).is_some_and(|c| c.is_ascii_digit()) {
+            is_float = true;
+            i += 1 + sign;
+            while is_digit(i) {
+                i += 1;
+            }
+        }
+    }
+
+    let mut text: String = chars[start..i].iter().collect();
+    if text.starts_with('.') {
+        text.insert(0, '0');
+    }
+
+    if is_float {
+        (TokenKind::Float(text), i)
+    } else {
+        (TokenKind::Integer(text), i)
+    }
+}
+
+// Вид строкового литерала
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum QuoteKind {
+    Text,
+    Bytes,
+    Format,
+}
+
+// Чтение содержимого строки в кавычках (без кавычек, escape-последовательности раскрываются).
+// В байтовой строке допустимы только ASCII-символы, а \xhh даёт символ с кодом hh.
+// У f-строки escape-последовательности только проверяются: позиции символов нужны для ошибок
+fn read_quoted(
+    chars: &[char],
+    open: usize,
+    quote: char,
+    kind: QuoteKind,
+    line_num: usize,
+    column: usize,
+) -> Result<(String, usize), TranspilerError> {
+    let mut i = open + 1;
+    let mut content = String::new();
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if i + 1 < chars.len() => {
+                let (decoded, next) = read_escape(chars, i + 1, kind == QuoteKind::Bytes).ok_or_else(|| {
+                    TranspilerError::new("Некорректная escape-последовательность", line_num, column + i)
+                        .with_code("syntax")
+                })?;
+                if kind == QuoteKind::Format {
+                    content.extend(&chars[i..next]);
+                } else {
+                    content.push_str(&decoded);
+                }
+                i = next;
+            }
+            c if c == quote => return Ok((content, i + 1)),
+            c if kind == QuoteKind::Bytes && !c.is_ascii() => {
+                return Err(TranspilerError::new(
+                    "Байтовая строка может содержать только ASCII-символы",
+                    line_num,
+                    column + i,
+                )
+                .with_code("syntax"));
+            }
+            c => {
+                content.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    Err(TranspilerError::new(
+        "Незакрытая строка",
+        line_num,
+        column + open,
+    )
+    .with_code("syntax"))
+}
+
+// Раскрытие escape-последовательностей в проверенном лексером тексте (текстовые части f-строк)
+pub fn decode_escapes(raw: &str) -> String {
+    let chars: Vec<char> = raw.chars().collect();
+    let mut result = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let escape = (chars[i] == '\\' && i + 1 < chars.len()).then(|| read_escape(&chars, i + 1, false));
+        match escape.flatten() {
+            Some((decoded, next)) => {
+                result.push_str(&decoded);
+                i = next;
+            }
+            None => {
+                result.push(chars[i]);
+                i += 1;
+            }
+        }
+    }
+    result
+}
+
+// Раскрытие escape-последовательности по правилам Python; start указывает на символ после '\\'.
+// Возвращает раскрытый текст и позицию после последовательности
+fn read_escape(chars: &[char], start: usize, bytes: bool) -> Option<(String, usize)> {
+    let hex_code = |len: usize| -> Option<u32> {
+        let digits: String = chars.get(start + 1..start + 1 + len)?.iter().collect();
+        if digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            u32::from_str_radix(&digits, 16).ok()
+        } else {
+            None
+        }
+    };
+
+    let simple = match chars[start] {
+        'n' => Some('\n'),
+        't' => Some('\t'),
+        'r' => Some('\r'),
+        'a' => Some('\x07'),
+        'b' => Some('\x08'),
+        'f' => Some('\x0c'),
+        'v' => Some('\x0b'),
+        c @ ('\\' | '\'' | '"') => Some(c),
+        _ => None,
+    };
+    if let Some(c) = simple {
+        return Some((c.to_string(), start + 1));
+    }
+
+    match chars[start] {
+        // Восьмеричный код: до трёх цифр
+        '0'..='7' => {
+            let len = chars[start..].iter().take(3).take_while(|c| ('0'..='7').contains(*c)).count();
+            let digits: String = chars[start..start + len].iter().collect();
+            let code = u32::from_str_radix(&digits, 8).ok()?;
+            if bytes && code > 0xff {
+                return None;
+            }
+            Some((char::from_u32(code)?.to_string(), start + len))
+        }
+        'x' => Some((char::from_u32(hex_code(2)?)?.to_string(), start + 3)),
+        'u' if !bytes => Some((char::from_u32(hex_code(4)?)?.to_string(), start + 5)),
+        'U' if !bytes => Some((char::from_u32(hex_code(8)?)?.to_string(), start + 9)),
+        // Неизвестная последовательность остаётся как есть, вместе с обратной косой чертой
+        c => Some((format!("\\{}", c), start + 1)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{tokenize, TokenKind};
+
+    fn kinds(expr: &str) -> Vec<TokenKind> {
+        tokenize(expr, 1, 1).expect("выражение должно разбираться на токены").into_iter().map(|token| token.kind).collect()
+    }
+
+    fn identifier(name: &str) -> TokenKind {
+        TokenKind::Identifier(name.to_string())
+    }
+
+    fn integer(value: &str) -> TokenKind {
+        TokenKind::Integer(value.to_string())
+    }
+
+    // Минус перед операндом - отдельный токен, а не часть числа или имени
+    #[test]
+    fn minus_is_separate_token() {
+        assert_eq!(
+            kinds("a - -b"),
+            vec![identifier("a"), TokenKind::Minus, TokenKind::Minus, identifier("b"), TokenKind::Eof]
+        );
+        assert_eq!(
+            kinds("2*-3"),
+            vec![integer("2"), TokenKind::Star, TokenKind::Minus, integer("3"), TokenKind::Eof]
+        );
+        assert_eq!(
+            kinds("10 - 2 - 3"),
+            vec![integer("10"), TokenKind::Minus, integer("2"), TokenKind::Minus, integer("3"), TokenKind::Eof]
+        );
+    }
+
+    // Знак экспоненты принадлежит числу
+    #[test]
+    fn exponent_sign_belongs_to_number() {
+        assert_eq!(kinds("1e-5"), vec![TokenKind::Float("1e-5".to_string()), TokenKind::Eof]);
+        assert_eq!(kinds("2E+3 - 1"), vec![TokenKind::Float("2E+3".to_string()), TokenKind::Minus, integer("1"), TokenKind::Eof]);
+    }
+
+    // Операторы внутри строки остаются текстом строки
+    #[test]
+    fn operators_inside_string() {
+        assert_eq!(kinds("\"a+b\""), vec![TokenKind::Str("a+b".to_string()), TokenKind::Eof]);
+        assert_eq!(kinds("b\"x-y\""), vec![TokenKind::Bytes("x-y".to_string()), TokenKind::Eof]);
+    }
+
+    // Позиции токенов - номера символов, а не байтов
+    #[test]
+    fn token_positions_in_chars() {
+        let tokens = tokenize("\"мир\" + x", 1, 1).unwrap();
+        assert_eq!((tokens[0].start, tokens[0].end), (0, 5));
+        assert_eq!((tokens[2].start, tokens[2].end), (8, 9));
+    }
+
+    // Незакрытая строка - синтаксическая ошибка
+    #[test]
+    fn unterminated_string() {
+        let error = tokenize("\"abc", 1, 1).unwrap_err();
+        assert_eq!(error.code, Some("syntax"));
+    }
+}
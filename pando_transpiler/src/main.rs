@@ -3,90 +3,132 @@ mod error;
 mod parser;
 mod generator;
 mod expressions;
+mod lexer;
+mod blocks;
+mod analysis;
+mod symbols;
 
 use std::fs;
-use std::collections::HashMap;
-use crate::error::TranspilerError;
-use crate::parser::parse_line;
-use crate::generator::generate_rust_line;
+use std::io::IsTerminal;
+use crate::error::{TranspilerError, Diagnostics};
+use crate::blocks::parse_program;
+use crate::analysis::analyze_program;
+use crate::symbols::SymbolTable;
+use crate::generator::generate_program;
 
-// Основная функция трансляции
-fn transpile_pd_to_rs(input_path: &str, output_path: &str) -> Result<(), TranspilerError> {
-    let content = fs::read_to_string(input_path)
-        .map_err(|e| TranspilerError::new(&format!("Ошибка чтения файла: {}", e), 1, 1))?;
-
-    let lines: Vec<&str> = content.lines().collect();
-    let mut rust_lines = Vec::new();
-    let mut variables = HashMap::new();
+// Основная функция трансляции; ошибки и предупреждения накапливаются в diagnostics
+fn transpile_pd_to_rs(lines: &[&str], output_path: &str, diagnostics: &mut Diagnostics) {
+    let mut symbols = SymbolTable::default();
     
-    for (i, line) in lines.iter().enumerate() {
-        let line_num = i + 1;
-        
-        match parse_line(line, line_num, &mut variables) {
-            Ok(parsed) => rust_lines.push(parsed),
-            Err(e) => return Err(e),
-        }
+    let mut rust_lines = parse_program(lines, &mut symbols, diagnostics);
+    if diagnostics.has_errors() {
+        return;
+    }
+    analyze_program(&mut rust_lines, diagnostics);
+    if diagnostics.has_errors() {
+        return;
     }
     
     // Проверяем, что есть хотя бы одна команда для выполнения
     let has_executable_code = rust_lines.iter().any(|line| {
-        matches!(line, types::ParsedLine::Print { .. } | types::ParsedLine::VariableDecl { .. })
+        !matches!(line, types::ParsedLine::Comment { .. } | types::ParsedLine::Empty | types::ParsedLine::Function { .. } | types::ParsedLine::Const { .. })
     });
     
     if !has_executable_code {
-        return Err(TranspilerError::new(
+        diagnostics.report(TranspilerError::new(
             "Файл не содержит команд для выполнения",
             1,
             1,
-        ));
+        )
+        .with_code("empty-program"));
+        return;
     }
 
     // Генерация Rust кода
-    let mut rust_code = String::from("fn main() {\n");
-    
-    for parsed in rust_lines {
-        let line = generate_rust_line(&parsed);
-        if line.is_empty() {
-            rust_code.push('\n');
-        } else {
-            rust_code.push_str(&format!("    {}\n", line));
-        }
-    }
-    
-    rust_code.push('}');
-
-    fs::write(output_path, rust_code)
-        .map_err(|e| TranspilerError::new(&format!("Ошибка записи файла: {}", e), 1, 1))?;
+    let rust_code = generate_program(&rust_lines);
 
-    println!("✅ Трансляция успешно завершена!");
-    println!("📁 Результат сохранён в: {}", output_path);
+    if let Err(e) = fs::write(output_path, rust_code) {
+        diagnostics.report(TranspilerError::new(&format!("Ошибка записи файла: {}", e), 1, 1).with_code("io"));
+    }
+}
 
-    Ok(())
+// Формат вывода сообщений об ошибках
+#[derive(PartialEq)]
+enum MessageFormat {
+    // Текст в стиле rustc для человека
+    Human,
+    // Один JSON-объект на строку для редакторов
+    Json,
 }
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
-    
-    if args.len() != 3 {
-        eprintln!("❌ Использование: {} <input.pd> <output.rs>", args[0]);
+    let usage = format!("❌ Использование: {} [--message-format=human|json] <input.pd> <output.rs>", args[0]);
+
+    let mut format = MessageFormat::Human;
+    let mut files = Vec::new();
+    for arg in &args[1..] {
+        match arg.strip_prefix("--message-format=") {
+            Some("human") => format = MessageFormat::Human,
+            Some("json") => format = MessageFormat::Json,
+            Some(other) => {
+                eprintln!("❌ Неизвестный формат сообщений: {}", other);
+                std::process::exit(1);
+            }
+            None => files.push(arg),
+        }
+    }
+
+    if files.len() != 2 {
+        eprintln!("{}", usage);
         std::process::exit(1);
     }
     
-    let input_file = &args[1];
-    let output_file = &args[2];
+    let input_file = files[0];
+    let output_file = files[1];
 
-    println!("🎯 Начинаю трансляцию {} -> {}", input_file, output_file);
+    if format == MessageFormat::Human {
+        println!("🎯 Начинаю трансляцию {} -> {}", input_file, output_file);
+    }
 
-    match transpile_pd_to_rs(input_file, output_file) {
-        Ok(_) => {
-            println!("\n✅ Трансляция успешна. Файл: {}", output_file);
-        }
+    let mut diagnostics = Diagnostics::default();
+    let content = match fs::read_to_string(input_file) {
+        Ok(content) => content,
         Err(e) => {
-            eprintln!(
-                "❌ Ошибка трансляции: {}",
-                e
-            );
+            diagnostics.report(TranspilerError::new(&format!("Ошибка чтения файла: {}", e), 1, 1).with_code("io"));
+            String::new()
+        }
+    };
+    let lines: Vec<&str> = content.lines().collect();
+    if !diagnostics.has_errors() {
+        transpile_pd_to_rs(&lines, output_file, &mut diagnostics);
+    }
+
+    let (errors, warnings) = (diagnostics.error_count(), diagnostics.warning_count());
+
+    if format == MessageFormat::Json {
+        for message in diagnostics.sorted() {
+            println!("{}", message.to_json(input_file));
+        }
+        if errors > 0 {
             std::process::exit(1);
         }
+        return;
     }
-}
\ No newline at end of file
+
+    // Цветной вывод только в терминал (и если он не отключён через NO_COLOR)
+    let color = std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    for message in diagnostics.sorted() {
+        eprintln!("{}", message.render(input_file, &lines, color));
+    }
+
+    if errors > 0 || warnings > 0 {
+        eprintln!("Ошибок: {}, предупреждений: {}", errors, warnings);
+    }
+
+    if errors > 0 {
+        std::process::exit(1);
+    }
+    println!("✅ Трансляция успешно завершена!");
+    println!("📁 Результат сохранён в: {}", output_file);
+}
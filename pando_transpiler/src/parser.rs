@@ -1,11 +1,13 @@
 use crate::error::TranspilerError;
-use crate::types::{ParsedLine, get_type_mapping};
-use crate::expressions::parse_expression;
-use std::collections::HashMap;
+use crate::types::{Span, ParsedLine, Expression, BinaryOperator, BlockHeader, ForIterable, get_type_mapping, is_copy_type, is_integer_type, parse_function_type, constant_integer, list_element_type, dict_types, set_element_type, tuple_element_types, tuple_type, is_hashable_type, is_text_type, canonical_type};
+use crate::expressions::{coerce_expression, is_operator_valid_for_type, parse_expression, split_arguments, split_call, top_level_positions};
+use crate::lexer::compound_assign_operator;
+use crate::symbols::{Symbol, SymbolKind, SymbolTable};
 
-// Функция для разделения строки на код и комментарий
+// Функция для разделения строки на код и комментарий; # внутри строкового или
+// символьного литерала комментарий не начинает, а кавычка другого вида литерал не закрывает
 pub fn split_code_and_comment(line: &str) -> (String, Option<String>) {
-    let mut in_string = false;
+    let mut quote: Option<char> = None;
     let mut escaped = false;
     let mut code_part = String::new();
     let mut comment_start = None;
@@ -22,20 +24,20 @@ pub fn split_code_and_comment(line: &str) -> (String, Option<String>) {
         }
         
         match c {
-            '\\' => {
+            '\\' if quote.is_some() => {
                 escaped = true;
                 code_part.push(c);
             }
             '"' | '\'' => {
-                in_string = !in_string;
+                match quote {
+                    None => quote = Some(c),
+                    Some(open) if open == c => quote = None,
+                    Some(_) => {}
+                }
                 code_part.push(c);
             }
-            '#' => {
-                if !in_string {
-                    comment_start = Some(code_part.len());
-                } else {
-                    code_part.push(c);
-                }
+            '#' if quote.is_none() => {
+                comment_start = Some(code_part.len());
             }
             _ => {
                 code_part.push(c);
@@ -55,17 +57,36 @@ pub fn split_code_and_comment(line: &str) -> (String, Option<String>) {
     (code_part, comment_part)
 }
 
+// Столбец (с 1, в символах), с которого фрагмент начинается в строке;
+// фрагмент должен быть подстрокой line
+pub fn column_of(line: &str, fragment: &str) -> usize {
+    let offset = fragment.as_ptr() as usize - line.as_ptr() as usize;
+    line[..offset].chars().count() + 1
+}
+
+// Положение фрагмента строки в исходном коде
+pub fn span_of(line: &str, fragment: &str, line_num: usize) -> Span {
+    let start = column_of(line, fragment);
+    Span::new(line_num, start, start + fragment.chars().count())
+}
+
+// Положение кода строки (без отступа и комментария)
+pub fn code_span(line: &str, line_num: usize) -> Span {
+    let (code_part, _) = split_code_and_comment(line);
+    span_of(line, line[..code_part.len()].trim(), line_num)
+}
+
 // Функция для парсинга одной строки
 pub fn parse_line(
     line: &str, 
     line_num: usize, 
-    variables: &mut HashMap<String, String>
+    symbols: &mut SymbolTable
 ) -> Result<ParsedLine, TranspilerError> {
-    let indent = line.chars().take_while(|c| c.is_whitespace()).count();
     let (code_part, comment_part) = split_code_and_comment(line);
     
-    let trimmed_code = code_part.trim();
+    let trimmed_code = line[..code_part.len()].trim();
     let comment_trimmed = comment_part.map(|c| c.trim_start().to_string());
+    let span = code_span(line, line_num);
     
     // Обработка пустых строк
     if trimmed_code.is_empty() {
@@ -73,12 +94,10 @@ pub fn parse_line(
             if comment.is_empty() {
                 return Ok(ParsedLine::Comment {
                     content: "//".to_string(),
-                    indent,
                 });
             } else {
                 return Ok(ParsedLine::Comment {
                     content: format!("// {}", comment),
-                    indent,
                 });
             }
         } else {
@@ -86,142 +105,1213 @@ pub fn parse_line(
         }
     }
     
-    // Проверяем, начинается ли строка с print
-    if trimmed_code.starts_with("print") {
-        // Проверяем наличие скобок
-        if !trimmed_code.contains('(') || !trimmed_code.contains(')') {
-            return Err(TranspilerError::new(
-                "Отсутствуют скобки у вызова print",
-                line_num,
-                trimmed_code.find('p').unwrap_or(1),
-            ));
-        }
-
-        // Извлекаем аргументы из скобок
-        let args_start = trimmed_code.find('(').unwrap();
-        let args_end = trimmed_code.find(')').unwrap();
-        let args = &trimmed_code[args_start + 1..args_end].trim();
-
-        // Проверяем что аргумент - строка в двойных кавычках
-        if !args.starts_with('"') || !args.ends_with('"') {
-            return Err(TranspilerError::new(
-                "Аргумент print должен быть строкой в двойных кавычках",
-                line_num,
-                args_start + 1,
-            ));
-        }
-
-        // Извлекаем содержимое строки (без кавычек)
-        let string_content = &args[1..args.len() - 1];
-        let escaped_content = crate::types::escape_string_for_rust(string_content);
-        
-        return Ok(ParsedLine::Print {
-            content: escaped_content,
+    if trimmed_code == "pass" {
+        return Ok(ParsedLine::Pass { comment: comment_trimmed, span });
+    }
+    
+    // Управление циклом
+    if trimmed_code == "break" {
+        return Ok(ParsedLine::Break {
+            label: None,
             comment: comment_trimmed,
-            indent,
+            span,
+        });
+    }
+    if trimmed_code == "continue" {
+        return Ok(ParsedLine::Continue {
+            label: None,
+            comment: comment_trimmed,
+            span,
         });
     }
     
-    // Пытаемся распарсить как объявление переменной
-    // Формат: имя: тип [= значение]
-    if let Some(colon_pos) = trimmed_code.find(':') {
-        let var_name = trimmed_code[..colon_pos].trim().to_string();
-        
-        // Проверяем корректность имени переменной
-        if var_name.is_empty() {
-            return Err(TranspilerError::new(
-                "Отсутствует имя переменной",
-                line_num,
-                1,
-            ));
-        }
-        
-        if !var_name.chars().next().unwrap().is_alphabetic() {
-            return Err(TranspilerError::new(
-                "Имя переменной должно начинаться с буквы",
-                line_num,
-                1,
-            ));
-        }
-        
-        let after_colon = trimmed_code[colon_pos + 1..].trim();
-        
-        // Ищем тип и опциональное значение
-        let parts: Vec<&str> = after_colon.splitn(2, '=').collect();
-        let type_part = parts[0].trim();
-        
-        // Проверяем, что тип известен
-        if get_type_mapping(type_part).is_none() {
-            return Err(TranspilerError::new(
-                &format!("Неизвестный тип: {}", type_part),
-                line_num,
-                colon_pos + 2,
-            ));
-        }
-        
-        // Добавляем переменную в таблицу символов
-        variables.insert(var_name.clone(), type_part.to_string());
-        
-        let value = if parts.len() > 1 {
-            let value_str = parts[1].trim();
-            Some(parse_expression(value_str, variables, line_num, colon_pos + parts[0].len() + 2)?)
-        } else {
+    // Возврат из функции
+    if let Some(rest) = strip_keyword(trimmed_code, "return") {
+        let rest = rest.trim();
+        let value = if rest.is_empty() {
             None
+        } else {
+            Some(parse_expression(rest, symbols, line_num, column_of(line, rest))?)
         };
-        
-        return Ok(ParsedLine::VariableDecl {
-            name: var_name,
-            type_name: type_part.to_string(),
+        return Ok(ParsedLine::Return {
+            value,
+            comment: comment_trimmed,
+            span,
+        });
+    }
+    
+    // Вывод: print(значения, sep=" ", end="\n")
+    if strip_keyword(trimmed_code, "print").is_some() {
+        let args = match split_call(trimmed_code) {
+            Some((_, args)) => args,
+            None => return Err(TranspilerError::at("Отсутствуют скобки у вызова print", span).with_code("syntax")),
+        };
+        return parse_print(line, args, symbols, line_num, comment_trimmed, span);
+    }
+    
+    // Константа: const ИМЯ: тип = значение
+    if let Some(rest) = strip_keyword(trimmed_code, "const") {
+        return parse_constant(line, rest.trim(), symbols, line_num, comment_trimmed, span);
+    }
+    
+    // Удаление элемента: del a[i], del d[k]
+    if let Some(rest) = strip_keyword(trimmed_code, "del") {
+        return parse_delete(line, rest.trim(), symbols, line_num, comment_trimmed, span);
+    }
+    
+    // Вызов функции как отдельная инструкция
+    if split_call(trimmed_code).is_some() {
+        return Ok(ParsedLine::ExpressionStatement {
+            expr: parse_expression(trimmed_code, symbols, line_num, span.start)?,
+            comment: comment_trimmed,
+            span,
+        });
+    }
+    
+    // Распаковка кортежа: a, b = b, a
+    let tuple_assignment = split_assignment(trimmed_code).filter(|(target, _, _)| split_arguments(target).len() > 1);
+    if let Some((targets, op, value_str)) = tuple_assignment.filter(|_| !is_annotated_declaration(trimmed_code)) {
+        if op.is_some() {
+            return Err(TranspilerError::at("Составное присваивание недопустимо для нескольких переменных", span)
+                .with_code("invalid-assignment"));
+        }
+        return parse_tuple_assignment(line, targets, value_str, symbols, line_num, comment_trimmed);
+    }
+    
+    // Присваивание элементу списка: a[i] = значение, a[i] += значение
+    let item_assignment = split_assignment(trimmed_code).filter(|(target, _, _)| target.ends_with(']'));
+    if let Some((target, op, value_str)) = item_assignment.filter(|_| !is_annotated_declaration(trimmed_code)) {
+        return parse_item_assignment(line, target, op, value_str, symbols, line_num, comment_trimmed);
+    }
+    
+    // Вызов метода как отдельная инструкция: a.append(x)
+    if trimmed_code.contains('.') && trimmed_code.ends_with(')') && split_assignment(trimmed_code).is_none() {
+        let expr = parse_expression(trimmed_code, symbols, line_num, span.start)?;
+        if matches!(expr, Expression::MethodCall { .. }) {
+            return Ok(ParsedLine::ExpressionStatement {
+                expr,
+                comment: comment_trimmed,
+                span,
+            });
+        }
+    }
+    
+    // Составное присваивание: имя op= значение
+    if let Some((var_name, op, value_str)) = split_compound_assignment(trimmed_code) {
+        let value = parse_compound_value(line, var_name, op, value_str, symbols, line_num)?;
+        return Ok(ParsedLine::CompoundAssign {
+            name: var_name.to_string(),
+            op,
             value,
             comment: comment_trimmed,
-            indent,
+            span,
         });
     }
+    
+    // Объявление переменной: имя: тип [= значение]
+    if is_annotated_declaration(trimmed_code) {
+        return parse_declaration(line, trimmed_code, symbols, line_num, comment_trimmed, span);
+    }
 
-    // Пытаемся распарсить как присваивание: x = значение или составное присваивание
+    // Пытаемся распарсить как присваивание: x = значение
     if let Some(equals_pos) = trimmed_code.find('=') {
         let left_side = trimmed_code[..equals_pos].trim();
         let right_side = trimmed_code[equals_pos + 1..].trim();
         
         // Проверяем, что слева от = допустимое имя переменной
-        if !left_side.is_empty() && left_side.chars().next().unwrap().is_alphabetic() {
-            // Проверяем, объявлена ли переменная
-            if !variables.contains_key(left_side) {
-                return Err(TranspilerError::new(
-                    &format!("Переменная '{}' не объявлена", left_side),
-                    line_num,
-                    1,
-                ));
-            }
+        if left_side.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_') {
+            // Первое присваивание необъявленной переменной объявляет её с выведенным типом
+            let Some(symbol) = symbols.lookup(left_side) else {
+                return parse_inferred_declaration(line, left_side, right_side, symbols, line_num, comment_trimmed);
+            };
             
             // Получаем тип переменной
-            let var_type = variables.get(left_side).unwrap().clone();
+            let var_type = assignable_type(symbol, left_side, span_of(line, left_side, line_num))?;
             
             // Парсим выражение
-            let value = parse_expression(right_side, variables, line_num, equals_pos + 1)?;
+            let value = parse_expression(right_side, symbols, line_num, column_of(line, right_side))?;
+            let value = coerce_expression(value, &var_type)?;
             let value_type = value.get_type().to_string();
             
             // Проверяем совместимость типов
             if var_type != value_type {
-                return Err(TranspilerError::new(
+                return Err(TranspilerError::at(
                     &format!("Несовместимые типы: нельзя присвоить {} в {}", value_type, var_type),
-                    line_num,
-                    equals_pos + 1,
-                ));
+                    value.span(),
+                )
+                .with_code("type-mismatch")
+                .with_label(span_of(line, left_side, line_num), &format!("переменная имеет тип {}", var_type)));
             }
             
             return Ok(ParsedLine::VariableAssign {
                 name: left_side.to_string(),
                 value,
                 comment: comment_trimmed,
-                indent,
+                span,
             });
         }
     }
     
-    Err(TranspilerError::new(
+    Err(TranspilerError::at(
         "Нераспознанная конструкция. Ожидается print, объявление или присваивание переменной",
-        line_num,
-        1,
-    ))
-}
\ No newline at end of file
+        span,
+    )
+    .with_code("syntax"))
+}
+
+// Объявление с аннотацией типа: двоеточие стоит раньше присваивания и скобок
+// (в b = a[1:3] двоеточие относится к срезу)
+fn is_annotated_declaration(code: &str) -> bool {
+    code.find(':').is_some_and(|colon| !code[..colon].contains(['=', '[', '(', '"', '\'']))
+}
+
+// Разбор объявления переменной: имя: тип [= значение]
+fn parse_declaration(
+    line: &str,
+    code: &str,
+    symbols: &mut SymbolTable,
+    line_num: usize,
+    comment: Option<String>,
+    span: Span,
+) -> Result<ParsedLine, TranspilerError> {
+    let (var_name, type_name, type_span, value_str) = parse_annotation(line, code, line_num, span)?;
+    let value = parse_annotation_value(line, value_str, &type_name, type_span, symbols, line_num);
+    
+    // Переменная становится видна только после разбора значения. При ошибке в значении
+    // она всё равно объявляется с указанным типом, чтобы следующие строки не сообщали,
+    // что переменная не объявлена
+    let declared = symbols.declare_variable(var_name, &type_name, span_of(line, var_name, line_num));
+    let value = value?;
+    declared?;
+    
+    Ok(ParsedLine::VariableDecl {
+        name: var_name.to_string(),
+        type_name,
+        value,
+        mutable: false,
+        comment,
+        span,
+    })
+}
+
+// Разбор имени, типа (вместе с местом его указания) и текста необязательного значения: имя: тип [= значение]
+fn parse_annotation<'a>(
+    line: &str,
+    code: &'a str,
+    line_num: usize,
+    span: Span,
+) -> Result<(&'a str, String, Span, Option<&'a str>), TranspilerError> {
+    let Some(colon_pos) = code.find(':') else {
+        return Err(TranspilerError::at("Ожидается объявление вида имя: тип = значение", span).with_code("syntax"));
+    };
+    let var_name = code[..colon_pos].trim();
+    
+    // Проверяем корректность имени переменной
+    if var_name.is_empty() {
+        return Err(TranspilerError::new(
+            "Отсутствует имя переменной",
+            line_num,
+            span.start,
+        )
+        .with_code("syntax"));
+    }
+    
+    if !var_name.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_') {
+        return Err(TranspilerError::at(
+            "Имя переменной должно начинаться с буквы или _",
+            span_of(line, var_name, line_num),
+        )
+        .with_code("syntax"));
+    }
+    
+    let after_colon = &code[colon_pos + 1..];
+    
+    // Ищем тип и опциональное значение
+    let parts: Vec<&str> = after_colon.splitn(2, '=').collect();
+    let type_span = span_of(line, parts[0].trim(), line_num);
+    let type_part = canonical_type(parts[0]);
+    
+    // Проверяем, что тип известен
+    check_type_name(&type_part, type_span)?;
+    
+    Ok((var_name, type_part, type_span, parts.get(1).map(|value_str| value_str.trim())))
+}
+
+// Разбор необязательного значения объявления с приведением к указанному типу
+fn parse_annotation_value(
+    line: &str,
+    value_str: Option<&str>,
+    type_name: &str,
+    type_span: Span,
+    symbols: &SymbolTable,
+    line_num: usize,
+) -> Result<Option<Expression>, TranspilerError> {
+    let Some(value_str) = value_str else {
+        return Ok(None);
+    };
+    let value = parse_expression(value_str, symbols, line_num, column_of(line, value_str))?;
+    let value = coerce_expression(value, type_name)?;
+    
+    // Проверяем совместимость типов
+    if value.get_type() != type_name {
+        return Err(TranspilerError::at(
+            &format!("Несовместимые типы: нельзя присвоить {} в {}", value.get_type(), type_name),
+            value.span(),
+        )
+        .with_code("type-mismatch")
+        .with_label(type_span, "ожидаемый тип указан здесь"));
+    }
+    Ok(Some(value))
+}
+
+// Разбор константы: объявление с обязательным значением, известным на этапе трансляции
+fn parse_constant(
+    line: &str,
+    code: &str,
+    symbols: &SymbolTable,
+    line_num: usize,
+    comment: Option<String>,
+    span: Span,
+) -> Result<ParsedLine, TranspilerError> {
+    // Константа добавляется в таблицу символов при сборе объявлений верхнего уровня
+    let (name, type_name, type_span, value_str) = parse_annotation(line, code, line_num, span)?;
+    let value = parse_annotation_value(line, value_str, &type_name, type_span, symbols, line_num)?;
+    
+    // Изменяемые и владеющие данными значения (строки string, коллекции) константами быть не могут
+    if !is_copy_type(&type_name) {
+        let error = TranspilerError::at(&format!("Константа не может иметь тип {}", type_name), type_span)
+            .with_code("type-mismatch");
+        return Err(match type_name.as_str() {
+            "string" => error.with_help("используйте неизменяемый тип str"),
+            "bytearray" => error.with_help("используйте неизменяемый тип bytes"),
+            _ => error,
+        });
+    }
+    
+    let Some(value) = value else {
+        return Err(TranspilerError::at(&format!("Константа '{}' должна иметь значение", name), span)
+            .with_code("syntax")
+            .with_help(&format!("const {}: {} = значение", name, type_name)));
+    };
+    if !is_compile_time_value(&value) {
+        return Err(TranspilerError::at(
+            "Значение константы должно быть известно на этапе трансляции",
+            value.span(),
+        )
+        .with_code("invalid-context")
+        .with_help("используйте литерал или выражение из литералов"));
+    }
+    
+    Ok(ParsedLine::Const {
+        name: name.to_string(),
+        type_name,
+        value,
+        comment,
+        span,
+    })
+}
+
+// Известно ли значение на этапе трансляции: литерал, выражение из литералов или кортеж из них
+fn is_compile_time_value(expr: &Expression) -> bool {
+    match expr {
+        Expression::Literal { .. } => true,
+        Expression::Tuple { elements, .. } => elements.iter().all(is_compile_time_value),
+        _ => expr.is_constant(),
+    }
+}
+
+// Тип переменной, которой присваивается значение; константы и функции изменять нельзя
+fn assignable_type(symbol: &Symbol, name: &str, name_span: Span) -> Result<String, TranspilerError> {
+    let (message, label) = match symbol.kind {
+        SymbolKind::Variable => return Ok(symbol.type_name.clone()),
+        SymbolKind::Constant => (format!("Нельзя изменить константу '{}'", name), "константа объявлена здесь"),
+        SymbolKind::Function => (format!("Нельзя присвоить значение функции '{}'", name), "функция объявлена здесь"),
+    };
+    Err(TranspilerError::at(&message, name_span)
+        .with_code("invalid-assignment")
+        .with_label(symbol.span, label))
+}
+
+// Объявление без аннотации: имя = значение, тип берётся из значения
+fn parse_inferred_declaration(
+    line: &str,
+    var_name: &str,
+    value_str: &str,
+    symbols: &mut SymbolTable,
+    line_num: usize,
+    comment: Option<String>,
+) -> Result<ParsedLine, TranspilerError> {
+    let name_span = span_of(line, var_name, line_num);
+    if !is_valid_identifier(var_name) {
+        return Err(TranspilerError::at("Некорректное имя переменной", name_span).with_code("syntax"));
+    }
+    
+    let value = parse_expression(value_str, symbols, line_num, column_of(line, value_str))?;
+    let value_type = value.get_type().to_string();
+    if value_type == "None" || parse_function_type(&value_type).is_some() {
+        return Err(TranspilerError::at(
+            &format!("Невозможно вывести тип переменной '{}' из значения типа {}", var_name, value_type),
+            value.span(),
+        )
+        .with_code("type-mismatch")
+        .with_label(name_span, "объявляемая переменная"));
+    }
+    
+    if value_type.contains('?') {
+        let (collection, example) = if dict_types(&value_type).is_some() {
+            ("словаря", "dict[str, int] = {}")
+        } else if set_element_type(&value_type).is_some() {
+            ("множества", "set[int] = set()")
+        } else {
+            ("списка", "list[int] = []")
+        };
+        return Err(TranspilerError::at(
+            &format!("Невозможно вывести тип переменной '{}' из пустого {}", var_name, collection),
+            value.span(),
+        )
+        .with_code("type-mismatch")
+        .with_help(&format!("укажите тип: {}: {}", var_name, example)));
+    }
+    
+    symbols.declare_variable(var_name, &value_type, name_span)?;
+    Ok(ParsedLine::VariableDecl {
+        name: var_name.to_string(),
+        type_name: value_type,
+        value: Some(value),
+        mutable: false,
+        comment,
+        span: code_span(line, line_num),
+    })
+}
+
+// Присваивание элементу списка: a[i] = значение, a[i] += значение
+fn parse_item_assignment(
+    line: &str,
+    target_str: &str,
+    op: Option<BinaryOperator>,
+    value_str: &str,
+    symbols: &SymbolTable,
+    line_num: usize,
+    comment: Option<String>,
+) -> Result<ParsedLine, TranspilerError> {
+    let target = parse_expression(target_str, symbols, line_num, column_of(line, target_str))?;
+    if !matches!(target, Expression::Index { .. }) || target.root_variable().is_none() {
+        let message = match target {
+            Expression::Slice { .. } => "Присваивание срезу не поддерживается",
+            _ => "Присвоить значение можно только переменной или элементу списка или словаря в переменной",
+        };
+        return Err(TranspilerError::at(message, target.span()).with_code("invalid-assignment"));
+    }
+    check_immutable_item(&target, false)?;
+    
+    let element_type = target.get_type().to_string();
+    let value = parse_expression(value_str, symbols, line_num, column_of(line, value_str))?;
+    let value = coerce_expression(value, &element_type)?;
+    if value.get_type() != element_type {
+        return Err(TranspilerError::at(
+            &format!("Несовместимые типы: нельзя присвоить {} в элемент типа {}", value.get_type(), element_type),
+            value.span(),
+        )
+        .with_code("type-mismatch")
+        .with_label(target.span(), &format!("элемент имеет тип {}", element_type)));
+    }
+    
+    if let Some(op) = op.filter(|op| !is_operator_valid_for_type(*op, &element_type)) {
+        return Err(TranspilerError::at(
+            &format!("Операция {}= недопустима для типа {}", op.as_str(), element_type),
+            code_span(line, line_num),
+        )
+        .with_code("unsupported-operation"));
+    }
+    
+    Ok(ParsedLine::ItemAssign {
+        target,
+        op,
+        value,
+        comment,
+        span: code_span(line, line_num),
+    })
+}
+
+// Кортеж, строка и bytes неизменяемы: их элементы нельзя заменить или удалить
+fn check_immutable_item(target: &Expression, deleting: bool) -> Result<(), TranspilerError> {
+    let Expression::Index { target: collection, .. } = target else {
+        return Ok(());
+    };
+    let collection_type = collection.get_type();
+    let (assigned, deleted, note) = if tuple_element_types(collection_type).is_some() {
+        ("Элементу кортежа нельзя присвоить значение", "Элемент кортежа нельзя удалить", "кортеж неизменяем; соберите новый кортеж")
+    } else if is_text_type(collection_type) {
+        ("Символу строки нельзя присвоить значение", "Символ строки нельзя удалить", "строка неизменяема; соберите новую строку из срезов")
+    } else if collection_type == "bytes" {
+        ("Байту bytes нельзя присвоить значение", "Байт bytes нельзя удалить", "bytes неизменяем; изменяемые байты - тип bytearray")
+    } else {
+        return Ok(());
+    };
+    let message = if deleting { deleted } else { assigned };
+    Err(TranspilerError::at(message, target.span()).with_code("invalid-assignment").with_note(note))
+}
+
+// Разбор распаковки кортежа: a, b = значение. Существующим переменным присваиваются
+// элементы кортежа, новые имена объявляются с типами элементов, _ пропускает элемент
+fn parse_tuple_assignment(
+    line: &str,
+    targets_str: &str,
+    value_str: &str,
+    symbols: &mut SymbolTable,
+    line_num: usize,
+    comment: Option<String>,
+) -> Result<ParsedLine, TranspilerError> {
+    let targets = split_arguments(targets_str);
+    for (i, target) in targets.iter().enumerate() {
+        if !is_valid_identifier(target) {
+            return Err(TranspilerError::at("Распаковать кортеж можно только в переменные", span_of(line, target, line_num))
+                .with_code("invalid-assignment"));
+        }
+        if *target != "_" && targets[..i].contains(target) {
+            return Err(TranspilerError::at(
+                &format!("Переменная '{}' указана в распаковке дважды", target),
+                span_of(line, target, line_num),
+            )
+            .with_code("duplicate-definition"));
+        }
+    }
+    
+    let value = parse_expression(value_str, symbols, line_num, column_of(line, value_str))?;
+    let element_types = unpack_types(value.get_type(), targets.len(), value.span())?;
+    
+    // Существующие переменные задают типы элементов, остальные элементы сохраняют свои типы
+    let mut expected_types = Vec::new();
+    for (target, element_type) in targets.iter().zip(&element_types) {
+        let expected = match symbols.lookup(target) {
+            Some(symbol) if *target != "_" => assignable_type(symbol, target, span_of(line, target, line_num))?,
+            _ => element_type.clone(),
+        };
+        expected_types.push(expected);
+    }
+    let value = coerce_expression(value, &tuple_type(&expected_types))?;
+    let element_types = unpack_types(value.get_type(), targets.len(), value.span())?;
+    
+    let mut declared = Vec::new();
+    for ((target, element_type), expected) in targets.iter().zip(&element_types).zip(&expected_types) {
+        let target_span = span_of(line, target, line_num);
+        if element_type != expected {
+            return Err(TranspilerError::at(
+                &format!("Несовместимые типы: нельзя присвоить {} в {}", element_type, expected),
+                value.span(),
+            )
+            .with_code("type-mismatch")
+            .with_label(target_span, &format!("переменная имеет тип {}", expected)));
+        }
+        if *target == "_" || symbols.lookup(target).is_some() {
+            declared.push(None);
+            continue;
+        }
+        if element_type.contains('?') {
+            return Err(TranspilerError::at(
+                &format!("Невозможно вывести тип переменной '{}' из пустой коллекции", target),
+                value.span(),
+            )
+            .with_code("type-mismatch")
+            .with_label(target_span, "объявляемая переменная"));
+        }
+        declared.push(Some(element_type.clone()));
+    }
+    
+    // Новые переменные становятся видны только после разбора значения
+    for (target, declared_type) in targets.iter().zip(&declared) {
+        if let Some(declared_type) = declared_type {
+            symbols.declare_variable(target, declared_type, span_of(line, target, line_num))?;
+        }
+    }
+    
+    Ok(ParsedLine::TupleAssign {
+        mutable: vec![false; targets.len()],
+        targets: targets.iter().map(|target| target.to_string()).collect(),
+        target_spans: targets.iter().map(|target| span_of(line, target, line_num)).collect(),
+        declared,
+        value,
+        comment,
+        span: code_span(line, line_num),
+    })
+}
+
+// Разбор удаления: del a[i] удаляет элемент списка, del d[k] - пару словаря
+fn parse_delete(
+    line: &str,
+    target_str: &str,
+    symbols: &SymbolTable,
+    line_num: usize,
+    comment: Option<String>,
+    span: Span,
+) -> Result<ParsedLine, TranspilerError> {
+    if target_str.is_empty() {
+        return Err(TranspilerError::at("Ожидается 'del элемент'", span).with_code("syntax"));
+    }
+    let target = parse_expression(target_str, symbols, line_num, column_of(line, target_str))?;
+    if !matches!(target, Expression::Index { .. }) || target.root_variable().is_none() {
+        let error = TranspilerError::at(
+            "Удалить можно только элемент списка или словаря в переменной",
+            target.span(),
+        )
+        .with_code("invalid-assignment");
+        return Err(match target {
+            Expression::Slice { .. } => error.with_note("удаление среза не поддерживается"),
+            Expression::Variable { .. } => error.with_note("переменная существует до конца своего блока"),
+            _ => error,
+        });
+    }
+    check_immutable_item(&target, true)?;
+    Ok(ParsedLine::Delete { target, comment, span })
+}
+
+// Разбор аргументов print: позиционные значения и необязательные строки sep и end
+fn parse_print(
+    line: &str,
+    args: &str,
+    symbols: &SymbolTable,
+    line_num: usize,
+    comment: Option<String>,
+    span: Span,
+) -> Result<ParsedLine, TranspilerError> {
+    let mut values = Vec::new();
+    let mut sep = None;
+    let mut end = None;
+    
+    for arg in split_arguments(args) {
+        let Some((keyword, value_str)) = split_keyword_argument(arg) else {
+            if sep.is_some() || end.is_some() {
+                return Err(TranspilerError::at(
+                    "Позиционный аргумент после именованного",
+                    span_of(line, arg, line_num),
+                )
+                .with_code("syntax"));
+            }
+            let value = parse_expression(arg, symbols, line_num, column_of(line, arg))?;
+            if parse_function_type(value.get_type()).is_some() {
+                return Err(TranspilerError::at(
+                    &format!("Значение типа {} нельзя вывести", value.get_type()),
+                    value.span(),
+                )
+                .with_code("type-mismatch"));
+            }
+            values.push(value);
+            continue;
+        };
+        
+        let keyword_span = span_of(line, keyword, line_num);
+        let slot = match keyword {
+            "sep" => &mut sep,
+            "end" => &mut end,
+            _ => {
+                return Err(TranspilerError::at(
+                    &format!("Неизвестный именованный аргумент print: {}", keyword),
+                    keyword_span,
+                )
+                .with_code("syntax")
+                .with_help("print принимает именованные аргументы sep и end"));
+            }
+        };
+        if slot.is_some() {
+            return Err(TranspilerError::at(
+                &format!("Аргумент {} указан повторно", keyword),
+                keyword_span,
+            )
+            .with_code("syntax"));
+        }
+        
+        let value = parse_expression(value_str, symbols, line_num, column_of(line, value_str))?;
+        if !matches!(value.get_type(), "str" | "string") {
+            return Err(TranspilerError::at(
+                &format!("Аргумент {} должен быть строкой, получен {}", keyword, value.get_type()),
+                value.span(),
+            )
+            .with_code("type-mismatch"));
+        }
+        *slot = Some(value);
+    }
+    
+    Ok(ParsedLine::Print {
+        args: values,
+        sep,
+        end,
+        comment,
+        span,
+    })
+}
+
+// Разделение именованного аргумента имя=значение (но не сравнения имя == значение)
+fn split_keyword_argument(arg: &str) -> Option<(&str, &str)> {
+    let (name, value) = arg.split_once('=')?;
+    let name = name.trim();
+    if !is_valid_identifier(name) || value.starts_with('=') {
+        return None;
+    }
+    Some((name, value.trim()))
+}
+
+// Разделение присваивания по первому оператору = или op= вне скобок и строк
+// на цель, оператор составного присваивания и значение
+fn split_assignment(code: &str) -> Option<(&str, Option<BinaryOperator>, &str)> {
+    for pos in top_level_positions(code) {
+        let rest = &code[pos..];
+        if let Some((op, op_len)) = compound_assign_operator(rest) {
+            return Some((code[..pos].trim(), Some(op), rest[op_len..].trim()));
+        }
+        // = не должно быть частью ==, !=, <= или >=
+        let previous = code[..pos].chars().next_back();
+        if rest.starts_with('=') && !rest.starts_with("==") && !matches!(previous, Some('=' | '!' | '<' | '>')) {
+            return Some((code[..pos].trim(), None, rest[1..].trim()));
+        }
+    }
+    None
+}
+
+// Разделение составного присваивания на имя переменной, оператор и значение
+fn split_compound_assignment(code: &str) -> Option<(&str, BinaryOperator, &str)> {
+    let name_end = code.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(code.len());
+    let var_name = &code[..name_end];
+    let rest = code[name_end..].trim_start();
+    let (op, op_len) = compound_assign_operator(rest)?;
+    if !is_valid_identifier(var_name) {
+        return None;
+    }
+    Some((var_name, op, rest[op_len..].trim()))
+}
+
+// Разбор значения составного присваивания с проверкой типов
+fn parse_compound_value(
+    line: &str,
+    var_name: &str,
+    op: BinaryOperator,
+    value_str: &str,
+    symbols: &SymbolTable,
+    line_num: usize,
+) -> Result<Expression, TranspilerError> {
+    let name_span = span_of(line, var_name, line_num);
+    let var_type = match symbols.lookup(var_name) {
+        Some(symbol) => assignable_type(symbol, var_name, name_span)?,
+        None => {
+            return Err(TranspilerError::at(&format!("Переменная '{}' не объявлена", var_name), name_span)
+                .with_code("undeclared-name"));
+        }
+    };
+    
+    if value_str.is_empty() {
+        return Err(TranspilerError::new(
+            &format!("Ожидается значение после '{}='", op.as_str()),
+            line_num,
+            code_span(line, line_num).end,
+        )
+        .with_code("syntax"));
+    }
+    
+    let value = parse_expression(value_str, symbols, line_num, column_of(line, value_str))?;
+    let value = coerce_expression(value, &var_type)?;
+    if value.get_type() != var_type {
+        return Err(TranspilerError::at(
+            &format!("Несовместимые типы: {} и {}", var_type, value.get_type()),
+            value.span(),
+        )
+        .with_code("type-mismatch")
+        .with_label(name_span, &format!("переменная имеет тип {}", var_type)));
+    }
+    
+    if !is_operator_valid_for_type(op, &var_type) {
+        let error = TranspilerError::at(
+            &format!("Операция {}= недопустима для типа {}", op.as_str(), var_type),
+            code_span(line, line_num),
+        )
+        .with_code("unsupported-operation");
+        return Err(match var_type.as_str() {
+            "str" if op == BinaryOperator::Add => error.with_help("дополнять можно строку типа string: объявите переменную как имя: string"),
+            _ => error,
+        });
+    }
+    
+    Ok(value)
+}
+
+// Вычисление отступа строки (количество ведущих пробельных символов)
+pub fn line_indent(line: &str) -> usize {
+    line.chars().take_while(|c| c.is_whitespace()).count()
+}
+
+// Проверка, что строка не содержит кода (пустая или только комментарий)
+pub fn is_blank_or_comment(line: &str) -> bool {
+    let (code_part, _) = split_code_and_comment(line);
+    code_part.trim().is_empty()
+}
+
+// Отделение ключевого слова от остальной части строки
+pub fn strip_keyword<'a>(code: &'a str, keyword: &str) -> Option<&'a str> {
+    let rest = code.strip_prefix(keyword)?;
+    match rest.chars().next() {
+        None => Some(rest),
+        Some(c) if c.is_whitespace() || c == '(' || c == ':' => Some(rest),
+        _ => None,
+    }
+}
+
+// Функция для парсинга заголовка блока (if/elif/else/while/for/def)
+pub fn parse_block_header(
+    line: &str,
+    line_num: usize,
+    symbols: &SymbolTable,
+) -> Result<Option<(BlockHeader, Option<String>)>, TranspilerError> {
+    let (code_part, comment_part) = split_code_and_comment(line);
+    let trimmed_code = line[..code_part.len()].trim();
+    let comment_trimmed = comment_part.map(|c| c.trim_start().to_string());
+
+    let (keyword, rest) = if let Some(rest) = strip_keyword(trimmed_code, "if") {
+        ("if", rest)
+    } else if let Some(rest) = strip_keyword(trimmed_code, "elif") {
+        ("elif", rest)
+    } else if let Some(rest) = strip_keyword(trimmed_code, "else") {
+        ("else", rest)
+    } else if let Some(rest) = strip_keyword(trimmed_code, "while") {
+        ("while", rest)
+    } else if let Some(rest) = strip_keyword(trimmed_code, "for") {
+        ("for", rest)
+    } else if let Some(rest) = strip_keyword(trimmed_code, "def") {
+        ("def", rest)
+    } else {
+        return Ok(None);
+    };
+
+    // Тело блока на строке заголовка (if x: print(x)) не поддерживается
+    let inline_body = top_level_positions(rest)
+        .into_iter()
+        .find(|&i| rest[i..].starts_with(':') && !rest[i + 1..].trim().is_empty());
+    if let Some(colon) = inline_body {
+        let body = rest[colon + 1..].trim();
+        return Err(TranspilerError::at(
+            &format!("Тело блока '{}' на строке заголовка не поддерживается", keyword),
+            span_of(line, body, line_num),
+        )
+        .with_code("syntax")
+        .with_help("перенесите тело на следующую строку с отступом; пустое тело записывается как pass"));
+    }
+    
+    // Заголовок блока должен завершаться двоеточием
+    let rest = match rest.trim_end().strip_suffix(':') {
+        Some(rest) => rest.trim(),
+        None => {
+            let end = span_of(line, trimmed_code, line_num).end;
+            return Err(TranspilerError::new(
+                &format!("Ожидается ':' в конце заголовка '{}'", keyword),
+                line_num,
+                end,
+            )
+            .with_code("syntax")
+            .with_fix("добавьте ':'", Span::new(line_num, end, end), ":"));
+        }
+    };
+
+    let header = match keyword {
+        "if" | "elif" | "while" => {
+            if rest.is_empty() {
+                return Err(TranspilerError::at(
+                    &format!("Отсутствует условие после '{}'", keyword),
+                    span_of(line, &trimmed_code[..keyword.len()], line_num),
+                )
+                .with_code("syntax"));
+            }
+
+            let condition = parse_expression(rest, symbols, line_num, column_of(line, rest))?;
+            let condition_type = condition.get_type();
+            if condition_type != "bool" {
+                let mut error = TranspilerError::at(
+                    &format!("Условие должно иметь тип bool, получен {}", condition_type),
+                    condition.span(),
+                )
+                .with_code("type-mismatch")
+                .with_note("неявное приведение к bool не выполняется");
+                if is_integer_type(condition_type) {
+                    let comparison = format!("{} != 0", rest);
+                    error = error.with_fix(
+                        &format!("для проверки на ноль используйте сравнение: {}", comparison),
+                        condition.span(),
+                        &comparison,
+                    );
+                }
+                return Err(error);
+            }
+
+            match keyword {
+                "if" => BlockHeader::If { condition },
+                "elif" => BlockHeader::Elif { condition },
+                _ => BlockHeader::While { condition },
+            }
+        }
+        "for" => parse_for_header(line, rest, symbols, line_num)?,
+        "def" => parse_def_header(line, rest, line_num, false)?,
+        _ => {
+            if !rest.is_empty() {
+                return Err(TranspilerError::at(
+                    "После 'else' ожидается ':'",
+                    span_of(line, rest, line_num),
+                )
+                .with_code("syntax"));
+            }
+            BlockHeader::Else
+        }
+    };
+
+    Ok(Some((header, comment_trimmed)))
+}
+
+// Проверка корректности имени переменной
+fn is_valid_identifier(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+// Типы элементов кортежа типа value_type, распаковываемого в count переменных
+fn unpack_types(value_type: &str, count: usize, span: Span) -> Result<Vec<String>, TranspilerError> {
+    let Some(element_types) = tuple_element_types(value_type) else {
+        return Err(TranspilerError::at(&format!("Распаковать можно только кортеж, получен {}", value_type), span)
+            .with_code("type-mismatch"));
+    };
+    if element_types.len() != count {
+        return Err(TranspilerError::at(
+            &format!(
+                "Нельзя распаковать {} в {} переменные: в кортеже {} элемент(ов)",
+                value_type,
+                count,
+                element_types.len()
+            ),
+            span,
+        )
+        .with_code("type-mismatch"));
+    }
+    Ok(element_types)
+}
+
+// Парсинг заголовка цикла for: for имя in источник
+fn parse_for_header(
+    line: &str,
+    rest: &str,
+    symbols: &SymbolTable,
+    line_num: usize,
+) -> Result<BlockHeader, TranspilerError> {
+    let (var_part, iter_part) = match rest.split_once(" in ") {
+        Some(parts) => parts,
+        None => {
+            return Err(TranspilerError::at(
+                "Ожидается 'for имя in источник:'",
+                span_of(line, rest, line_num),
+            )
+            .with_code("syntax"));
+        }
+    };
+
+    let mut var_names: Vec<&str> = Vec::new();
+    for var_name in var_part.split(',').map(str::trim) {
+        if !is_valid_identifier(var_name) {
+            return Err(TranspilerError::at(
+                &format!("Некорректное имя переменной цикла: '{}'", var_name),
+                span_of(line, var_part.trim(), line_num),
+            )
+            .with_code("syntax"));
+        }
+        if var_name != "_" && var_names.contains(&var_name) {
+            return Err(TranspilerError::at(
+                &format!("Переменная цикла '{}' указана дважды", var_name),
+                span_of(line, var_part.trim(), line_num),
+            )
+            .with_code("duplicate-definition"));
+        }
+        var_names.push(var_name);
+    }
+
+    let iter_part = iter_part.trim();
+
+    // Несколько переменных распаковывают элементы-кортежи: for k, v in d.items()
+    if var_names.len() > 1 {
+        let iterable_expr = parse_expression(iter_part, symbols, line_num, column_of(line, iter_part))?;
+        let iterable_type = iterable_expr.get_type();
+        let Some(element_type) = list_element_type(iterable_type).or(set_element_type(iterable_type)) else {
+            let error = TranspilerError::at(
+                &format!("Несколько переменных цикла требуют элементов-кортежей, а перебирается {}", iterable_type),
+                iterable_expr.span(),
+            )
+            .with_code("type-mismatch");
+            return Err(match dict_types(iterable_type) {
+                Some(_) => error.with_help("пары словаря перебираются так: for k, v in d.items():"),
+                None => error,
+            });
+        };
+        let var_types = unpack_types(element_type, var_names.len(), iterable_expr.span())?;
+        return Ok(BlockHeader::For {
+            vars: var_names
+                .iter()
+                .zip(var_types)
+                .map(|(name, var_type)| (name.to_string(), var_type, span_of(line, name, line_num)))
+                .collect(),
+            iterable: ForIterable::List(iterable_expr),
+        });
+    }
+    let var_name = var_names[0].to_string();
+    let var_span = span_of(line, var_names[0], line_num);
+
+    // range(stop), range(start, stop), range(start, stop, step)
+    if let Some(args_part) = iter_part.strip_prefix("range(").and_then(|a| a.strip_suffix(')')) {
+        let args = split_arguments(args_part);
+        if args.is_empty() || args.len() > 3 {
+            return Err(TranspilerError::at(
+                &format!("range принимает от 1 до 3 аргументов, получено {}", args.len()),
+                span_of(line, iter_part, line_num),
+            )
+            .with_code("invalid-range"));
+        }
+
+        let mut arg_exprs = args
+            .iter()
+            .map(|arg| parse_expression(arg, symbols, line_num, column_of(line, arg)))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Шаг - целочисленная константа, к типу переменной цикла он не приводится
+        let step_expr = if arg_exprs.len() == 3 { arg_exprs.pop() } else { None };
+
+        // Общий тип задаёт первый неконстантный аргумент, а если все аргументы константы -
+        // первая константа, не поместившаяся в int
+        let common_type = arg_exprs
+            .iter()
+            .find(|arg| !arg.is_constant())
+            .or_else(|| arg_exprs.iter().find(|arg| arg.get_type() != "int"))
+            .map_or("int", |arg| arg.get_type())
+            .to_string();
+
+        let mut parsed_args: Vec<Expression> = Vec::new();
+        for arg_expr in arg_exprs {
+            let arg_expr = coerce_expression(arg_expr, &common_type)?;
+            if !is_integer_type(arg_expr.get_type()) {
+                return Err(TranspilerError::at(
+                    &format!("Аргументы range должны быть целыми числами, получен {}", arg_expr.get_type()),
+                    arg_expr.span(),
+                )
+                .with_code("type-mismatch"));
+            }
+            if let Some(first) = parsed_args.first() {
+                if first.get_type() != arg_expr.get_type() {
+                    return Err(TranspilerError::at(
+                        &format!("Несовместимые типы аргументов range: {} и {}", first.get_type(), arg_expr.get_type()),
+                        arg_expr.span(),
+                    )
+                    .with_code("type-mismatch"));
+                }
+            }
+            parsed_args.push(arg_expr);
+        }
+
+        let var_type = parsed_args[0].get_type().to_string();
+        let step = match &step_expr {
+            Some(step_expr) => match constant_integer(step_expr) {
+                Some(0) => {
+                    return Err(TranspilerError::at(
+                        "Шаг range не может быть равен нулю",
+                        step_expr.span(),
+                    )
+                    .with_code("invalid-range"));
+                }
+                Some(step) => step,
+                None => {
+                    return Err(TranspilerError::at(
+                        "Шаг range должен быть целочисленной константой",
+                        step_expr.span(),
+                    )
+                    .with_code("invalid-range"));
+                }
+            },
+            None => 1,
+        };
+
+        let mut parsed_args = parsed_args.into_iter();
+        let (start, stop) = if args.len() == 1 {
+            let start = Expression::Literal {
+                value: "0".to_string(),
+                expr_type: var_type.clone(),
+                span: span_of(line, iter_part, line_num),
+            };
+            (start, parsed_args.next().unwrap())
+        } else {
+            (parsed_args.next().unwrap(), parsed_args.next().unwrap())
+        };
+
+        return Ok(BlockHeader::For {
+            vars: vec![(var_name, var_type, var_span)],
+            iterable: ForIterable::Range { start, stop, step },
+        });
+    }
+
+    // Итерация по символам строки, байтам, элементам списка или множества, ключам словаря
+    let iterable_expr = parse_expression(iter_part, symbols, line_num, column_of(line, iter_part))?;
+    let iterable_span = iterable_expr.span();
+    let (var_type, iterable) = match iterable_expr.get_type() {
+        "str" | "string" => ("char", ForIterable::Chars(iterable_expr)),
+        "bytes" | "bytearray" => ("uint8", ForIterable::Bytes(iterable_expr)),
+        other if other.contains('?') => {
+            return Err(TranspilerError::at("Тип элементов пустой коллекции неизвестен", iterable_span)
+                .with_code("type-mismatch"));
+        }
+        other if list_element_type(other).is_some() || set_element_type(other).is_some() => {
+            let element_type = list_element_type(other).or(set_element_type(other)).unwrap_or_default().to_string();
+            return Ok(BlockHeader::For {
+                vars: vec![(var_name, element_type, var_span)],
+                iterable: ForIterable::List(iterable_expr),
+            });
+        }
+        other if dict_types(other).is_some() => {
+            let (key_type, _) = dict_types(other).unwrap_or_default();
+            return Ok(BlockHeader::For {
+                vars: vec![(var_name, key_type, var_span)],
+                iterable: ForIterable::Keys(iterable_expr),
+            });
+        }
+        other => {
+            return Err(TranspilerError::at(
+                &format!("Тип {} не поддерживает итерацию", other),
+                iterable_span,
+            )
+            .with_code("type-mismatch"));
+        }
+    };
+
+    Ok(BlockHeader::For {
+        vars: vec![(var_name, var_type.to_string(), var_span)],
+        iterable,
+    })
+}
+
+// Проверка, что имя типа известно; ключом словаря и элементом множества может быть только хешируемый тип
+fn check_type_name(type_name: &str, span: Span) -> Result<(), TranspilerError> {
+    if let Some(element_type) = set_element_type(type_name) {
+        if !is_hashable_type(element_type) && get_type_mapping(element_type).is_some() {
+            return Err(TranspilerError::at(&format!("Тип {} не может быть элементом множества", element_type), span)
+                .with_code("unknown-type")
+                .with_note("элементом может быть целое число, bool, char, строка или bytes"));
+        }
+    }
+    if let Some((key_type, _)) = dict_types(type_name) {
+        if !is_hashable_type(&key_type) && get_type_mapping(&key_type).is_some() {
+            return Err(TranspilerError::at(&format!("Тип {} не может быть ключом словаря", key_type), span)
+                .with_code("unknown-type")
+                .with_note("ключом может быть целое число, bool, char, строка или bytes"));
+        }
+    }
+    if get_type_mapping(type_name).is_none() {
+        return Err(TranspilerError::at(
+            &format!("Неизвестный тип: {}", type_name),
+            span,
+        )
+        .with_code("unknown-type"));
+    }
+    Ok(())
+}
+
+// Парсинг заголовка функции: def имя(параметр: тип, ...) -> тип
+// Разбор заголовка функции; при recovering повторяющиеся имена параметров не считаются ошибкой
+fn parse_def_header(
+    line: &str,
+    rest: &str,
+    line_num: usize,
+    recovering: bool,
+) -> Result<BlockHeader, TranspilerError> {
+    let (signature, return_part) = match rest.rsplit_once("->") {
+        Some((signature, return_part)) => (signature.trim(), Some(return_part.trim())),
+        None => (rest, None),
+    };
+
+    let (name, params_part) = match split_call(signature) {
+        Some(parts) => parts,
+        None => {
+            return Err(TranspilerError::at(
+                "Ожидается 'def имя(параметры) -> тип:'",
+                span_of(line, rest, line_num),
+            )
+            .with_code("syntax"));
+        }
+    };
+
+    let mut params: Vec<(String, String, Span)> = Vec::new();
+    for param in split_arguments(params_part) {
+        let (param_name, param_type) = match param.split_once(':') {
+            Some((param_name, param_type)) => (param_name.trim(), param_type.trim()),
+            None => {
+                return Err(TranspilerError::at(
+                    &format!("Для параметра '{}' не указан тип", param),
+                    span_of(line, param, line_num),
+                )
+                .with_code("syntax"));
+            }
+        };
+
+        if !is_valid_identifier(param_name) {
+            return Err(TranspilerError::at(
+                &format!("Некорректное имя параметра: '{}'", param_name),
+                span_of(line, param_name, line_num),
+            )
+            .with_code("syntax"));
+        }
+        if !recovering && params.iter().any(|(existing, _, _)| existing == param_name) {
+            return Err(TranspilerError::at(
+                &format!("Параметр '{}' указан несколько раз", param_name),
+                span_of(line, param_name, line_num),
+            )
+            .with_code("duplicate-definition"));
+        }
+        let param_type_name = canonical_type(param_type);
+        check_type_name(&param_type_name, span_of(line, param_type, line_num))?;
+
+        params.push((param_name.to_string(), param_type_name, span_of(line, param_name, line_num)));
+    }
+
+    // Без аннотации функция ничего не возвращает
+    let return_type = match return_part {
+        Some(return_part) => {
+            let return_type = canonical_type(return_part);
+            check_type_name(&return_type, span_of(line, return_part, line_num))?;
+            return_type
+        }
+        None => "None".to_string(),
+    };
+
+    Ok(BlockHeader::Def {
+        name: name.to_string(),
+        params,
+        return_type,
+    })
+}
+
+// Сигнатура функции из заголовка с ошибкой: если имя и типы разобрать удалось
+// (ошибка в двоеточии, теле на строке заголовка или повторе параметра), функция
+// объявляется, чтобы её вызовы не давали лишних ошибок
+pub fn recover_def_header(line: &str, line_num: usize) -> Option<BlockHeader> {
+    let (code_part, _) = split_code_and_comment(line);
+    let rest = strip_keyword(line[..code_part.len()].trim(), "def")?;
+    let rest = match top_level_positions(rest).into_iter().find(|&i| rest[i..].starts_with(':')) {
+        Some(colon) => &rest[..colon],
+        None => rest,
+    };
+    parse_def_header(line, rest.trim(), line_num, true).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_code_and_comment;
+
+    // # внутри строки с апострофом не начинает комментарий
+    #[test]
+    fn comment_after_string_with_other_quote() {
+        let (code, comment) = split_code_and_comment("print(\"it's # not a comment\")  # real");
+        assert_eq!(code, "print(\"it's # not a comment\")  ");
+        assert_eq!(comment.as_deref(), Some(" real"));
+    }
+
+    // Экранированная кавычка не закрывает строку, # в символьном литерале - не комментарий
+    #[test]
+    fn comment_after_escaped_quote_and_char() {
+        assert_eq!(split_code_and_comment("x = \"a\\\"#b\"").1, None);
+        assert_eq!(split_code_and_comment("c = '#' # символ").0, "c = '#' ");
+    }
+}
@@ -0,0 +1,104 @@
+use crate::error::TranspilerError;
+use crate::types::Span;
+use std::collections::HashMap;
+
+// Вид объявленного имени
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SymbolKind {
+    Variable,
+    Constant,
+    Function,
+}
+
+// Объявленное имя: вид, тип и место объявления
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub kind: SymbolKind,
+    pub type_name: String,
+    pub span: Span,
+}
+
+// Таблица символов. Функции и константы верхнего уровня видны везде,
+// переменные — в стеке областей видимости текущей функции (или основного кода):
+// каждый вложенный блок открывает новую область, которая закрывается вместе с ним
+#[derive(Debug, Clone)]
+pub struct SymbolTable {
+    items: HashMap<String, Symbol>,
+    scopes: Vec<HashMap<String, Symbol>>,
+}
+
+impl Default for SymbolTable {
+    fn default() -> Self {
+        SymbolTable {
+            items: HashMap::new(),
+            scopes: vec![HashMap::new()],
+        }
+    }
+}
+
+impl SymbolTable {
+    // Поиск имени: сначала во вложенных областях видимости, затем среди функций и констант
+    pub fn lookup(&self, name: &str) -> Option<&Symbol> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .or_else(|| self.items.get(name))
+    }
+
+    // Тип видимого имени
+    pub fn get_type(&self, name: &str) -> Option<&str> {
+        self.lookup(name).map(|symbol| symbol.type_name.as_str())
+    }
+
+    // Объявление функции или константы верхнего уровня (повторы проверяются при сборе объявлений)
+    pub fn declare_item(&mut self, name: &str, kind: SymbolKind, type_name: &str, span: Span) {
+        let symbol = Symbol { kind, type_name: type_name.to_string(), span };
+        self.items.insert(name.to_string(), symbol);
+    }
+
+    // Объявление переменной в текущей области видимости.
+    // Повторное объявление в той же области — ошибка; объявление с типом во вложенном блоке
+    // явно перекрывает переменную внешнего блока до конца этого блока
+    pub fn declare_variable(&mut self, name: &str, type_name: &str, span: Span) -> Result<(), TranspilerError> {
+        if let Some(item) = self.items.get(name) {
+            let (message, label) = match item.kind {
+                SymbolKind::Constant => ("константой", "константа объявлена здесь"),
+                _ => ("функцией", "функция объявлена здесь"),
+            };
+            return Err(TranspilerError::at(&format!("Имя '{}' уже занято {}", name, message), span)
+                .with_code("duplicate-definition")
+                .with_label(item.span, label));
+        }
+        let scope = self.scopes.last_mut().unwrap();
+        if let Some(previous) = scope.get(name) {
+            return Err(TranspilerError::at(&format!("Переменная '{}' уже объявлена", name), span)
+                .with_code("duplicate-definition")
+                .with_label(previous.span, "первое объявление здесь")
+                .with_help(&format!("чтобы изменить значение, используйте присваивание без типа: {} = значение", name)));
+        }
+        let symbol = Symbol { kind: SymbolKind::Variable, type_name: type_name.to_string(), span };
+        scope.insert(name.to_string(), symbol);
+        Ok(())
+    }
+
+    // Открытие области видимости вложенного блока
+    pub fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    // Закрытие области видимости: объявленные в блоке переменные больше не видны
+    pub fn pop_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    // Вход в тело функции: переменные внешнего кода в нём не видны
+    pub fn enter_function(&mut self) -> Vec<HashMap<String, Symbol>> {
+        std::mem::replace(&mut self.scopes, vec![HashMap::new()])
+    }
+
+    // Выход из тела функции с восстановлением областей видимости внешнего кода
+    pub fn leave_function(&mut self, saved: Vec<HashMap<String, Symbol>>) {
+        self.scopes = saved;
+    }
+}
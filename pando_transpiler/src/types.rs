@@ -1,60 +1,347 @@
 use std::collections::HashMap;
 
+// Положение фрагмента в исходном файле: строка и диапазон столбцов
+// (столбцы считаются в символах с 1, конец не включается)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(line: usize, start: usize, end: usize) -> Self {
+        Self { line, start, end }
+    }
+
+    // Фрагмент от начала self до конца other (на одной строке)
+    pub fn to(&self, other: Span) -> Span {
+        Span::new(self.line, self.start.min(other.start), self.end.max(other.end))
+    }
+}
+
 // Типы для представления строк кода
 #[derive(Debug, Clone)]
 pub enum ParsedLine {
     Print {
-        content: String,
+        args: Vec<Expression>,
+        sep: Option<Expression>,
+        end: Option<Expression>,
         comment: Option<String>,
-        indent: usize,
+        span: Span,
     },
     VariableDecl {
         name: String,
         type_name: String,
         value: Option<Expression>,
+        // Переприсваивается ли переменная (определяется анализом после разбора)
+        mutable: bool,
+        comment: Option<String>,
+        span: Span,
+    },
+    // Константа верхнего уровня: const ИМЯ: тип = значение
+    Const {
+        name: String,
+        type_name: String,
+        value: Expression,
         comment: Option<String>,
-        indent: usize,
+        span: Span,
     },
     VariableAssign {
         name: String,
         value: Expression,
         comment: Option<String>,
-        indent: usize,
+        span: Span,
+    },
+    // Распаковка кортежа: a, b = b, a; x, _ = point
+    TupleAssign {
+        targets: Vec<String>,
+        target_spans: Vec<Span>,
+        // Тип имени, которое объявляет распаковка; None - присваивание существующей переменной или _
+        declared: Vec<Option<String>>,
+        mutable: Vec<bool>,
+        value: Expression,
+        comment: Option<String>,
+        span: Span,
+    },
+    // Составное присваивание: x += 1, x //= 2
+    CompoundAssign {
+        name: String,
+        op: BinaryOperator,
+        value: Expression,
+        comment: Option<String>,
+        span: Span,
+    },
+    // Присваивание элементу списка или словаря: a[i] = v, d[k] += v (target - выражение Index)
+    ItemAssign {
+        target: Expression,
+        op: Option<BinaryOperator>,
+        value: Expression,
+        comment: Option<String>,
+        span: Span,
+    },
+    If {
+        branches: Vec<ConditionalBranch>,
+        else_branch: Option<Block>,
+        span: Span,
+    },
+    While {
+        condition: Expression,
+        body: Vec<ParsedLine>,
+        else_branch: Option<Block>,
+        loop_label: Option<String>,
+        else_label: Option<String>,
+        comment: Option<String>,
+        span: Span,
+    },
+    For {
+        // Переменные цикла: одна или несколько (for k, v in d.items())
+        var_names: Vec<String>,
+        var_spans: Vec<Span>,
+        // Переприсваивается ли каждая переменная цикла в теле
+        mutable: Vec<bool>,
+        iterable: ForIterable,
+        body: Vec<ParsedLine>,
+        else_branch: Option<Block>,
+        loop_label: Option<String>,
+        else_label: Option<String>,
+        comment: Option<String>,
+        span: Span,
+    },
+    Function {
+        name: String,
+        params: Vec<(String, String)>,
+        param_spans: Vec<Span>,
+        // Параметры, которые переприсваиваются в теле функции
+        mutable_params: Vec<String>,
+        return_type: String,
+        body: Vec<ParsedLine>,
+        comment: Option<String>,
+        span: Span,
+    },
+    Return {
+        value: Option<Expression>,
+        comment: Option<String>,
+        span: Span,
+    },
+    // Удаление элемента: del a[i], del d[k] (target - выражение Index)
+    Delete {
+        target: Expression,
+        comment: Option<String>,
+        span: Span,
+    },
+    ExpressionStatement {
+        expr: Expression,
+        comment: Option<String>,
+        span: Span,
+    },
+    Break {
+        label: Option<String>,
+        comment: Option<String>,
+        span: Span,
+    },
+    Continue {
+        label: Option<String>,
+        comment: Option<String>,
+        span: Span,
+    },
+    // Пустая инструкция pass: тело блока, в котором ничего не нужно делать
+    Pass {
+        comment: Option<String>,
+        span: Span,
     },
     Comment {
         content: String,
-        indent: usize,
     },
     Empty,
 }
 
+impl ParsedLine {
+    // Положение инструкции в исходном коде (у комментариев и пустых строк его нет)
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            ParsedLine::Print { span, .. }
+            | ParsedLine::VariableDecl { span, .. }
+            | ParsedLine::Const { span, .. }
+            | ParsedLine::VariableAssign { span, .. }
+            | ParsedLine::TupleAssign { span, .. }
+            | ParsedLine::CompoundAssign { span, .. }
+            | ParsedLine::ItemAssign { span, .. }
+            | ParsedLine::Delete { span, .. }
+            | ParsedLine::If { span, .. }
+            | ParsedLine::While { span, .. }
+            | ParsedLine::For { span, .. }
+            | ParsedLine::Function { span, .. }
+            | ParsedLine::Return { span, .. }
+            | ParsedLine::ExpressionStatement { span, .. }
+            | ParsedLine::Break { span, .. }
+            | ParsedLine::Continue { span, .. }
+            | ParsedLine::Pass { span, .. } => Some(*span),
+            ParsedLine::Comment { .. } | ParsedLine::Empty => None,
+        }
+    }
+}
+
+// Ветка условного оператора (if или elif)
+#[derive(Debug, Clone)]
+pub struct ConditionalBranch {
+    pub condition: Expression,
+    pub body: Vec<ParsedLine>,
+    pub comment: Option<String>,
+}
+
+// Тело блока без условия (например, else)
+#[derive(Debug, Clone)]
+pub struct Block {
+    pub body: Vec<ParsedLine>,
+    pub comment: Option<String>,
+}
+
+// Источник значений для цикла for
+#[derive(Debug, Clone)]
+pub enum ForIterable {
+    // range(start, stop, step); шаг известен на этапе трансляции
+    Range {
+        start: Expression,
+        stop: Expression,
+        step: i128,
+    },
+    // Символы строки (str или string)
+    Chars(Expression),
+    // Байты (bytes или bytearray)
+    Bytes(Expression),
+    // Элементы списка или множества
+    List(Expression),
+    // Ключи словаря
+    Keys(Expression),
+}
+
+// Заголовок блока (строка, завершающаяся двоеточием)
+#[derive(Debug, Clone)]
+pub enum BlockHeader {
+    If { condition: Expression },
+    Elif { condition: Expression },
+    Else,
+    While { condition: Expression },
+    // Имя, тип и положение каждой переменной цикла и каждого параметра
+    For { vars: Vec<(String, String, Span)>, iterable: ForIterable },
+    Def { name: String, params: Vec<(String, String, Span)>, return_type: String },
+}
+
 // Тип выражения
 #[derive(Debug, Clone)]
 pub enum Expression {
     Literal {
         value: String,
         expr_type: String,
+        span: Span,
     },
     Variable {
         name: String,
         expr_type: String,
+        span: Span,
     },
     BinaryOp {
         left: Box<Expression>,
         op: BinaryOperator,
         right: Box<Expression>,
         expr_type: String,
+        span: Span,
     },
     UnaryOp {
         op: UnaryOperator,
         expr: Box<Expression>,
         expr_type: String,
+        span: Span,
     },
-    CompoundAssign {
+    Call {
         name: String,
-        op: BinaryOperator,
+        args: Vec<Expression>,
+        expr_type: String,
+        span: Span,
+    },
+    // Явное преобразование в числовой тип: int64(x), int(s)
+    Conversion {
         value: Box<Expression>,
         expr_type: String,
+        span: Span,
+    },
+    // f-строка: f"Привет, {name}"; имеет тип string
+    FormatString {
+        parts: Vec<FormatPart>,
+        span: Span,
+    },
+    // Список: [1, 2, 3]; у пустого списка без контекста тип list[?]
+    List {
+        elements: Vec<Expression>,
+        expr_type: String,
+        span: Span,
+    },
+    // Множество: {1, 2} или set(); у пустого множества без контекста тип set[?]
+    Set {
+        elements: Vec<Expression>,
+        expr_type: String,
+        span: Span,
+    },
+    // Кортеж: (1, "a") или 1, "a" без скобок
+    Tuple {
+        elements: Vec<Expression>,
+        expr_type: String,
+        span: Span,
+    },
+    // Словарь: {"a": 1}; у пустого словаря без контекста тип dict[?, ?]
+    Dict {
+        entries: Vec<(Expression, Expression)>,
+        expr_type: String,
+        span: Span,
+    },
+    // Элемент по индексу: a[i] (отрицательный индекс отсчитывается с конца) или по ключу: d[k]
+    Index {
+        target: Box<Expression>,
+        index: Box<Expression>,
+        expr_type: String,
+        span: Span,
+    },
+    // Срез: a[start:stop], границы необязательны
+    Slice {
+        target: Box<Expression>,
+        start: Option<Box<Expression>>,
+        stop: Option<Box<Expression>>,
+        expr_type: String,
+        span: Span,
+    },
+    // Вызов метода: a.append(x)
+    MethodCall {
+        object: Box<Expression>,
+        method: String,
+        args: Vec<Expression>,
+        expr_type: String,
+        span: Span,
+    },
+    // Вызов встроенной функции: len(a)
+    BuiltinCall {
+        name: String,
+        args: Vec<Expression>,
+        expr_type: String,
+        span: Span,
+    },
+    // Значение, вычисляемое один раз и доступное в body под именем name:
+    // средний операнд цепочки 0 < f(x) < 10
+    Bound {
+        name: String,
+        value: Box<Expression>,
+        body: Box<Expression>,
+        span: Span,
+    },
+}
+
+// Часть f-строки: текст или подставляемое значение со спецификатором формата Rust
+#[derive(Debug, Clone)]
+pub enum FormatPart {
+    Text(String),
+    Value {
+        expr: Expression,
+        spec: String,
     },
 }
 
@@ -69,6 +356,19 @@ pub enum BinaryOperator {
     BitwiseOr,
     BitwiseAnd,
     BitwiseXor,
+    ShiftLeft,
+    ShiftRight,
+    Power,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    In,
+    NotIn,
+    And,
+    Or,
 }
 
 impl BinaryOperator {
@@ -83,11 +383,29 @@ impl BinaryOperator {
             BinaryOperator::BitwiseOr => "|",
             BinaryOperator::BitwiseAnd => "&",
             BinaryOperator::BitwiseXor => "^",
+            BinaryOperator::ShiftLeft => "<<",
+            BinaryOperator::ShiftRight => ">>",
+            BinaryOperator::Power => "**",
+            BinaryOperator::Equal => "==",
+            BinaryOperator::NotEqual => "!=",
+            BinaryOperator::Less => "<",
+            BinaryOperator::LessEqual => "<=",
+            BinaryOperator::Greater => ">",
+            BinaryOperator::GreaterEqual => ">=",
+            BinaryOperator::In => "in",
+            BinaryOperator::NotIn => "not in",
+            BinaryOperator::And => "and",
+            BinaryOperator::Or => "or",
         }
     }
     
-    pub fn len(&self) -> usize {
-        self.as_str().len()
+    pub fn is_comparison(&self) -> bool {
+        matches!(self,
+            BinaryOperator::Equal | BinaryOperator::NotEqual |
+            BinaryOperator::Less | BinaryOperator::LessEqual |
+            BinaryOperator::Greater | BinaryOperator::GreaterEqual |
+            BinaryOperator::In | BinaryOperator::NotIn
+        )
     }
 }
 
@@ -95,6 +413,7 @@ impl BinaryOperator {
 pub enum UnaryOperator {
     Negate,
     BitwiseNot,
+    Not,
 }
 
 impl Expression {
@@ -104,13 +423,100 @@ impl Expression {
             Expression::Variable { expr_type, .. } => expr_type,
             Expression::BinaryOp { expr_type, .. } => expr_type,
             Expression::UnaryOp { expr_type, .. } => expr_type,
-            Expression::CompoundAssign { expr_type, .. } => expr_type,
+            Expression::Call { expr_type, .. } => expr_type,
+            Expression::Conversion { expr_type, .. } => expr_type,
+            Expression::FormatString { .. } => "string",
+            Expression::List { expr_type, .. } => expr_type,
+            Expression::Set { expr_type, .. } => expr_type,
+            Expression::Tuple { expr_type, .. } => expr_type,
+            Expression::Dict { expr_type, .. } => expr_type,
+            Expression::Index { expr_type, .. } => expr_type,
+            Expression::Slice { expr_type, .. } => expr_type,
+            Expression::MethodCall { expr_type, .. } => expr_type,
+            Expression::BuiltinCall { expr_type, .. } => expr_type,
+            Expression::Bound { body, .. } => body.get_type(),
+        }
+    }
+    
+    pub fn span(&self) -> Span {
+        match self {
+            Expression::Literal { span, .. } => *span,
+            Expression::Variable { span, .. } => *span,
+            Expression::BinaryOp { span, .. } => *span,
+            Expression::UnaryOp { span, .. } => *span,
+            Expression::Call { span, .. } => *span,
+            Expression::Conversion { span, .. } => *span,
+            Expression::FormatString { span, .. } => *span,
+            Expression::List { span, .. } => *span,
+            Expression::Set { span, .. } => *span,
+            Expression::Tuple { span, .. } => *span,
+            Expression::Dict { span, .. } => *span,
+            Expression::Index { span, .. } => *span,
+            Expression::Slice { span, .. } => *span,
+            Expression::MethodCall { span, .. } => *span,
+            Expression::BuiltinCall { span, .. } => *span,
+            Expression::Bound { span, .. } => *span,
+        }
+    }
+    
+    // Переменная, часть которой обозначает выражение: a для a[i][j]
+    pub fn root_variable(&self) -> Option<&str> {
+        match self {
+            Expression::Variable { name, .. } => Some(name),
+            Expression::Index { target, .. } => target.root_variable(),
+            _ => None,
+        }
+    }
+    
+    // Константное выражение из числовых литералов: его тип выбирается по контексту
+    pub fn is_constant(&self) -> bool {
+        match self {
+            Expression::Literal { expr_type, .. } => is_numeric_type(expr_type),
+            Expression::UnaryOp { op, expr, .. } => *op != UnaryOperator::Not && expr.is_constant(),
+            Expression::BinaryOp { left, op, right, .. } => {
+                !op.is_comparison()
+                    && !matches!(op, BinaryOperator::And | BinaryOperator::Or)
+                    && left.is_constant()
+                    && right.is_constant()
+            }
+            _ => false,
         }
     }
 }
 
-// Маппинг типов Pando -> Rust
-pub fn get_type_mapping(type_name: &str) -> Option<&'static str> {
+// Маппинг типов Pando -> Rust; list[T] отображается в Vec<T>, set[T] - в упорядоченное
+// множество BTreeSet<T>, tuple[A, B] - в кортеж (A, B), а dict[K, V] - в пару
+// (HashMap<K, usize>, Vec<(K, V)>): как в Python, словарь хранит пары в порядке добавления,
+// а индекс по ключу даёт позицию пары в списке
+pub fn get_type_mapping(type_name: &str) -> Option<String> {
+    if let Some(element_type) = list_element_type(type_name) {
+        return get_element_mapping(element_type).map(|element| format!("Vec<{}>", element));
+    }
+    if let Some(element_type) = set_element_type(type_name) {
+        if !is_hashable_type(element_type) {
+            return None;
+        }
+        return get_element_mapping(element_type).map(|element| format!("std::collections::BTreeSet<{}>", element));
+    }
+    // Кортеж из одного элемента в Rust записывается с запятой: (i32,)
+    if let Some(element_types) = tuple_element_types(type_name) {
+        let elements = element_types.iter().map(|t| get_element_mapping(t)).collect::<Option<Vec<_>>>()?;
+        return match elements.as_slice() {
+            [] => None,
+            [element] => Some(format!("({},)", element)),
+            _ => Some(format!("({})", elements.join(", "))),
+        };
+    }
+    if let Some((key_type, value_type)) = dict_types(type_name) {
+        if !is_hashable_type(&key_type) {
+            return None;
+        }
+        let key = get_element_mapping(&key_type)?;
+        let value = get_element_mapping(&value_type)?;
+        return Some(format!("(std::collections::HashMap<{}, usize>, Vec<({}, {})>)", key, key, value));
+    }
+    
+
     let mapping: HashMap<&str, &str> = [
         ("int", "i32"),
         ("int8", "i8"),
@@ -131,7 +537,7 @@ pub fn get_type_mapping(type_name: &str) -> Option<&'static str> {
         ("char", "char"),
         ("str", "&str"),
         ("None", "()"),
-        ("bytes", "&[u8]"),
+        ("bytes", "Vec<u8>"),
         ("bytearray", "Vec<u8>"),
         ("string", "String"),
     ]
@@ -139,27 +545,82 @@ pub fn get_type_mapping(type_name: &str) -> Option<&'static str> {
     .cloned()
     .collect();
     
-    mapping.get(type_name).copied()
+    mapping.get(type_name).map(|rust_type| rust_type.to_string())
+}
+
+// Тип Rust для элемента коллекции: None и функции элементами быть не могут
+fn get_element_mapping(type_name: &str) -> Option<String> {
+    if type_name == "None" || parse_function_type(type_name).is_some() {
+        return None;
+    }
+    get_type_mapping(type_name)
 }
 
-// Значения по умолчанию для типов
-pub fn get_default_value(type_name: &str) -> String {
-    match type_name {
-        "int" | "int8" | "int16" | "int32" | "int64" | "int128" | "int_size" => "0".to_string(),
-        "uint8" | "uint16" | "uint32" | "uint64" | "uint128" | "uint_size" => "0".to_string(),
-        "float" => "0.0f32".to_string(),
-        "double" => "0.0f64".to_string(),
-        "bool" => "false".to_string(),
-        "char" => "'\\0'".to_string(),
-        "str" => "\"\"".to_string(),
-        "None" => "()".to_string(),
-        "bytes" => "b\"\"".to_string(),
-        "bytearray" => "Vec::new()".to_string(),
-        "string" => "String::new()".to_string(),
-        _ => "0".to_string(),
+// Запись типа в каноническом виде, в котором типы сравниваются:
+// без пробелов, кроме одного после каждой запятой (dict[str,int] -> dict[str, int])
+pub fn canonical_type(type_name: &str) -> String {
+    type_name.split_whitespace().collect::<String>().replace(',', ", ")
+}
+
+// Тип элементов списка: list[int] -> int
+pub fn list_element_type(type_name: &str) -> Option<&str> {
+    type_name.strip_prefix("list[")?.strip_suffix(']')
+}
+
+// Тип элементов множества: set[int] -> int
+pub fn set_element_type(type_name: &str) -> Option<&str> {
+    type_name.strip_prefix("set[")?.strip_suffix(']')
+}
+
+// Тип множества с элементами заданного типа
+pub fn set_type(element_type: &str) -> String {
+    format!("set[{}]", element_type)
+}
+
+// Типы элементов кортежа: tuple[int, str] -> [int, str]
+pub fn tuple_element_types(type_name: &str) -> Option<Vec<String>> {
+    let inner = type_name.strip_prefix("tuple[")?.strip_suffix(']')?;
+    Some(split_type_list(inner))
+}
+
+// Тип кортежа с элементами заданных типов
+pub fn tuple_type(element_types: &[String]) -> String {
+    format!("tuple[{}]", element_types.join(", "))
+}
+
+// Типы ключей и значений словаря: dict[str, int] -> (str, int)
+pub fn dict_types(type_name: &str) -> Option<(String, String)> {
+    let inner = type_name.strip_prefix("dict[")?.strip_suffix(']')?;
+    match split_type_list(inner).as_slice() {
+        [key_type, value_type] => Some((key_type.clone(), value_type.clone())),
+        _ => None,
     }
 }
 
+// Тип словаря с заданными типами ключей и значений
+pub fn dict_type(key_type: &str, value_type: &str) -> String {
+    format!("dict[{}, {}]", key_type, value_type)
+}
+
+// Может ли значение типа быть ключом словаря или элементом множества:
+// целые числа, bool, символы, строки и кортежи из них
+pub fn is_hashable_type(type_name: &str) -> bool {
+    if let Some(element_types) = tuple_element_types(type_name) {
+        return element_types.iter().all(|t| is_hashable_type(t));
+    }
+    is_integer_type(type_name) || matches!(type_name, "bool" | "char" | "str" | "string" | "bytes")
+}
+
+// Изменяет ли метод объект, у которого вызван (переменная со списком или множеством должна быть mut)
+pub fn is_mutating_method(method: &str) -> bool {
+    matches!(method, "append" | "pop" | "insert" | "remove" | "add" | "discard" | "extend")
+}
+
+// Тип списка с элементами заданного типа
+pub fn list_type(element_type: &str) -> String {
+    format!("list[{}]", element_type)
+}
+
 // Проверка, является ли тип числовым
 pub fn is_numeric_type(type_name: &str) -> bool {
     matches!(type_name,
@@ -169,6 +630,16 @@ pub fn is_numeric_type(type_name: &str) -> bool {
     )
 }
 
+// Проверка, является ли тип строковым: str (&str) или string (String)
+pub fn is_text_type(type_name: &str) -> bool {
+    matches!(type_name, "str" | "string")
+}
+
+// Проверка, является ли тип байтовым: bytes или bytearray (оба - Vec<u8>)
+pub fn is_bytes_type(type_name: &str) -> bool {
+    matches!(type_name, "bytes" | "bytearray")
+}
+
 // Проверка, является ли тип целочисленным
 pub fn is_integer_type(type_name: &str) -> bool {
     matches!(type_name,
@@ -177,11 +648,125 @@ pub fn is_integer_type(type_name: &str) -> bool {
     )
 }
 
+// Допустимый диапазон целочисленного типа: модуль минимального значения и максимальное значение
+pub fn integer_range(type_name: &str) -> Option<(u128, u128)> {
+    let range = match type_name {
+        "int8" => (1 << 7, i8::MAX as u128),
+        "int16" => (1 << 15, i16::MAX as u128),
+        "int" | "int32" => (1 << 31, i32::MAX as u128),
+        "int64" | "int_size" => (1 << 63, i64::MAX as u128),
+        "int128" => (1 << 127, i128::MAX as u128),
+        "uint8" => (0, u8::MAX as u128),
+        "uint16" => (0, u16::MAX as u128),
+        "uint32" => (0, u32::MAX as u128),
+        "uint64" | "uint_size" => (0, u64::MAX as u128),
+        "uint128" => (0, u128::MAX),
+        _ => return None,
+    };
+    Some(range)
+}
+
+// Помещается ли любое значение целого типа source в целый тип target
+pub fn is_integer_widening(source: &str, target: &str) -> bool {
+    match (integer_range(source), integer_range(target)) {
+        (Some((source_min, source_max)), Some((target_min, target_max))) => {
+            source_min <= target_min && source_max <= target_max
+        }
+        _ => false,
+    }
+}
+
+// Допустимые явные преобразования в числовой тип: из любого числа,
+// из bool (только в целые) и из строки (разбор числа)
+pub fn is_conversion_allowed(source: &str, target: &str) -> bool {
+    is_numeric_type(target)
+        && (is_numeric_type(source)
+            || matches!(source, "str" | "string")
+            || (source == "bool" && is_integer_type(target)))
+}
+
 // Проверка, является ли тип битовым (целочисленным без знака для битовых операций)
 pub fn is_bitwise_type(type_name: &str) -> bool {
     is_integer_type(type_name)
 }
 
+// Проверка, поддерживает ли тип упорядочивающие сравнения (<, <=, >, >=)
+// (списки и кортежи сравниваются поэлементно)
+pub fn is_ordered_type(type_name: &str) -> bool {
+    if let Some(element_types) = tuple_element_types(type_name) {
+        return element_types.iter().all(|t| is_ordered_type(t));
+    }
+    match list_element_type(type_name) {
+        Some(element_type) => is_ordered_type(element_type),
+        None => is_numeric_type(type_name) || matches!(type_name, "char" | "str" | "string" | "bool"),
+    }
+}
+
+// Изменяемая коллекция: в Python её значение разделяется между переменными,
+// а в Pando копируется при присваивании и передаче в функцию
+pub fn is_mutable_collection(type_name: &str) -> bool {
+    type_name == "bytearray"
+        || list_element_type(type_name).is_some()
+        || dict_types(type_name).is_some()
+        || set_element_type(type_name).is_some()
+}
+
+// Проверка, копируется ли значение типа при передаче (иначе нужен clone)
+pub fn is_copy_type(type_name: &str) -> bool {
+    if let Some(element_types) = tuple_element_types(type_name) {
+        return element_types.iter().all(|t| is_copy_type(t));
+    }
+    !matches!(type_name, "string" | "bytes" | "bytearray")
+        && list_element_type(type_name).is_none()
+        && dict_types(type_name).is_none()
+        && set_element_type(type_name).is_none()
+}
+
+// Значение целочисленной константы (литерал или литерал с унарным минусом)
+pub fn constant_integer(expr: &Expression) -> Option<i128> {
+    match expr {
+        Expression::Literal { value, expr_type, .. } if is_integer_type(expr_type) => value.parse().ok(),
+        Expression::UnaryOp { op: UnaryOperator::Negate, expr, .. } => constant_integer(expr).map(|v| -v),
+        _ => None,
+    }
+}
+
+// Тип функции в таблице символов: fn(int, str) -> int
+pub fn function_type(param_types: &[String], return_type: &str) -> String {
+    format!("fn({}) -> {}", param_types.join(", "), return_type)
+}
+
+// Разбор типа функции на типы параметров и тип результата
+pub fn parse_function_type(type_name: &str) -> Option<(Vec<String>, String)> {
+    let rest = type_name.strip_prefix("fn(")?;
+    let (params, return_type) = rest.split_once(") -> ")?;
+    Some((split_type_list(params), return_type.to_string()))
+}
+
+// Разбиение списка типов по запятым верхнего уровня (вне скобок)
+pub fn split_type_list(list: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut depth = 0;
+    let mut current = String::new();
+    for c in list.chars() {
+        match c {
+            '[' | '(' => depth += 1,
+            ']' | ')' => depth -= 1,
+            ',' if depth == 0 => {
+                result.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    if !current.trim().is_empty() {
+        result.push(current.trim().to_string());
+    }
+    result
+}
+
 // Функция для экранирования строки для Rust
 pub fn escape_string_for_rust(s: &str) -> String {
     let mut result = String::new();
@@ -192,8 +777,27 @@ pub fn escape_string_for_rust(s: &str) -> String {
             '\t' => result.push_str("\\t"),
             '"' => result.push_str("\\\""),
             '\\' => result.push_str("\\\\"),
+            c if c.is_control() => result.push_str(&format!("\\u{{{:x}}}", c as u32)),
             _ => result.push(c),
         }
     }
     result
+}
+
+// Экранирование содержимого байтовой строки: символы с кодами 0..=255 из лексера
+// записываются как ASCII или \xhh
+pub fn escape_bytes_for_rust(s: &str) -> String {
+    let mut result = String::new();
+    for c in s.chars() {
+        match c {
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            c if c.is_ascii() && !c.is_ascii_control() => result.push(c),
+            c => result.push_str(&format!("\\x{:02x}", c as u32)),
+        }
+    }
+    result
 }
\ No newline at end of file
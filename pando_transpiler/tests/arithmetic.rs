@@ -0,0 +1,30 @@
+// Целочисленное деление и остаток по правилам Python
+mod common;
+
+use common::run;
+
+// Частное округляется вниз, знак остатка совпадает со знаком делителя
+#[test]
+fn integer_floor_division_and_modulo() {
+    let source = "a: int = -7\nb: int = 2\nprint(a // b, a % b, 7 // -2, 7 % -2, -7 // 2, -7 % 2)\n";
+    assert_eq!(run(source), "-4 1 -4 -1 -4 1\n");
+}
+
+// Вещественное // и %, в том числе над константами
+#[test]
+fn float_floor_division_and_modulo() {
+    let source = "print(7.5 // 2, -7 // 2.0, 7.5 % 2, -7.5 % 2)\nx: double = -7.5\nprint(x // 2, x % 2)\n";
+    assert_eq!(run(source), "3.0 -4.0 1.5 0.5\n-4.0 0.5\n");
+}
+
+// Беззнаковые типы делятся операторами Rust
+#[test]
+fn unsigned_division() {
+    assert_eq!(run("a: uint8 = 7\nb: uint8 = 2\nprint(a // b, a % b)\n"), "3 1\n");
+}
+
+// Тип переменной цикла по range с константными границами не указан в коде Rust
+#[test]
+fn modulo_of_range_variable() {
+    assert_eq!(run("for i in range(-2, 3):\n    print(i % 3, i // 2, end=\" \")\n"), "1 -1 2 -1 0 0 1 0 2 1 ");
+}
@@ -0,0 +1,29 @@
+// Составное присваивание: x += 1, x //= 2 и остальные операторы
+mod common;
+
+use common::{errors, run};
+
+// Все целочисленные операторы, включая // и % по правилам Python, сдвиги и степень
+#[test]
+fn integer_operators() {
+    let source = "x: int = 7\nx += 3\nx -= 1\nx *= 2\nx //= -4\nprint(x)\n\
+                  x %= 3\nx **= 3\nx <<= 2\nx >>= 1\nx |= 1\nx &= 7\nx ^= 2\nprint(x)\n";
+    assert_eq!(run(source), "-5\n1\n");
+}
+
+// Вещественные числа, строки и элементы коллекций
+#[test]
+fn other_targets() {
+    let source = "f: double = 2.5\nf /= 2\nf **= 2\ns: string = \"a\"\ns += \"b\"\n\
+                  a: list[int] = [1, 2]\na[-1] *= 5\nd: dict[str, int] = {\"k\": 1}\nd[\"k\"] += 2\nprint(f, s, a, d)\n";
+    assert_eq!(run(source), "1.5625 ab [1, 10] {'k': 3}\n");
+}
+
+// Тип значения проверяется, а переменная должна быть объявлена
+#[test]
+fn compound_assignment_errors() {
+    let messages = errors("x: int = 1\nx += \"a\"\ny += 1\nb: bool = True\nb += 1\n");
+    assert!(messages.contains("Несовместимые типы: int и str"), "{}", messages);
+    assert!(messages.contains("Переменная 'y' не объявлена"), "{}", messages);
+    assert!(messages.contains("Несовместимые типы: bool и int"), "{}", messages);
+}
@@ -0,0 +1,32 @@
+// Блоки с отступами: if/elif/else, pass и сообщения о неверных заголовках
+mod common;
+
+use common::{errors, run};
+
+// Выбор ветки if/elif/else
+#[test]
+fn if_elif_else() {
+    let source = "x: int = 5\nif x > 10:\n    print(\"big\")\nelif x > 3:\n    print(\"medium\")\nelse:\n    print(\"small\")\n";
+    assert_eq!(run(source), "medium\n");
+}
+
+// pass - пустое тело блока
+#[test]
+fn pass_as_empty_body() {
+    let source = "x: int = 1\nif x > 0:\n    pass\nelse:\n    pass  # ничего\ndef f() -> None:\n    pass\nf()\nprint(x)\n";
+    assert_eq!(run(source), "1\n");
+}
+
+// Тело на строке заголовка - отдельная ошибка без предложения дописать ':'
+#[test]
+fn inline_body_is_reported() {
+    let messages = errors("if True: print(1)\n");
+    assert!(messages.contains("Тело блока 'if' на строке заголовка не поддерживается"), "{}", messages);
+    assert!(!messages.contains("Ожидается ':'"), "{}", messages);
+}
+
+// Заголовок без двоеточия
+#[test]
+fn missing_colon() {
+    assert!(errors("x: int = 1\nif x > 0\n    print(x)\n").contains("Ожидается ':' в конце заголовка 'if'"));
+}
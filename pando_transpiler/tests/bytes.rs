@@ -0,0 +1,35 @@
+// Байты bytes и bytearray: построение, сложение, срезы и методы
+mod common;
+
+use common::{run, run_failure};
+
+// Байты, собранные во время выполнения, можно дописывать, срезать и передавать в функцию
+#[test]
+fn runtime_bytes() {
+    let source = "data: bytes = b\"\"\nfor i in range(3):\n    data += b\"ab\"\ndata = data + bytes([65, 66])\n\
+                  def tail(b: bytes) -> bytes:\n    return b[1:]\n\n\
+                  print(data, len(data), data[1:3], data[-1], tail(data), b\"xy\"[1:])\n";
+    assert_eq!(run(source), "b'abababAB' 8 b'ba' 66 b'bababAB' b'y'\n");
+}
+
+// bytes и bytearray сравниваются и копируются друг в друга
+#[test]
+fn bytes_and_bytearray() {
+    let source = "arr: bytearray = bytearray(b\"hi\")\narr += b\"ab\"[:1]\narr.append(33)\n\
+                  print(arr, bytes(arr) == b\"hia!\", arr == b\"hia!\", \"hé\".encode(), b\"abc\".hex(), b\"abc\".decode())\n";
+    assert_eq!(run(source), "bytearray(b'hia!') True True b'h\\xc3\\xa9' 616263 abc\n");
+}
+
+// bytes может быть ключом словаря и элементом множества
+#[test]
+fn bytes_as_keys() {
+    let source = "d: dict[bytes, int] = {b\"a\": 1}\nd[b\"b\"] = 2\ns: set[bytes] = {b\"x\", b\"x\"}\nprint(d, b\"a\" in d, s)\n";
+    assert_eq!(run(source), "{b'a': 1, b'b': 2} True {b'x'}\n");
+}
+
+// Значение вне диапазона байта завершает программу
+#[test]
+fn byte_out_of_range() {
+    let messages = run_failure("values: list[int] = [1, 256]\nprint(bytes(values))\n");
+    assert!(messages.contains("Байт должен быть в диапазоне от 0 до 255"), "{}", messages);
+}
@@ -0,0 +1,100 @@
+// Общие функции тестов: трансляция программы Pando собранным транслятором,
+// компиляция результата rustc и запуск
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+// Отдельный каталог для каждой программы: тесты выполняются параллельно
+fn work_dir() -> PathBuf {
+    let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+    let dir = std::env::temp_dir().join(format!("pando_tests_{}_{}", std::process::id(), id));
+    std::fs::create_dir_all(&dir).expect("не удалось создать временный каталог");
+    dir
+}
+
+// Запуск транслятора с дополнительными аргументами; возвращает результат процесса
+// и путь к сгенерированному коду
+fn invoke(source: &str, options: &[&str]) -> (Output, PathBuf) {
+    let dir = work_dir();
+    let input = dir.join("main.pd");
+    let output = dir.join("main.rs");
+    std::fs::write(&input, source).expect("не удалось записать программу");
+    let result = Command::new(env!("CARGO_BIN_EXE_pando_transpiler"))
+        .args(options)
+        .arg(&input)
+        .arg(&output)
+        .env("NO_COLOR", "1")
+        .output()
+        .expect("не удалось запустить транслятор");
+    (result, output)
+}
+
+// Трансляция программы; возвращает сгенерированный код Rust или сообщения транслятора
+pub fn transpile(source: &str) -> Result<String, String> {
+    let (result, output) = invoke(source, &[]);
+    if !result.status.success() {
+        return Err(String::from_utf8_lossy(&result.stderr).into_owned());
+    }
+    Ok(std::fs::read_to_string(&output).expect("транслятор не записал результат"))
+}
+
+// Предупреждения транслятора о программе, которая транслируется успешно
+pub fn warnings(source: &str) -> String {
+    let (result, _) = invoke(source, &[]);
+    assert!(result.status.success(), "ошибка трансляции:\n{}", String::from_utf8_lossy(&result.stderr));
+    String::from_utf8_lossy(&result.stderr).into_owned()
+}
+
+// Сообщения транслятора в формате JSON, по объекту на строку
+pub fn json_messages(source: &str) -> Vec<String> {
+    let (result, _) = invoke(source, &["--message-format=json"]);
+    String::from_utf8_lossy(&result.stdout).lines().map(str::to_string).collect()
+}
+
+// Сообщения транслятора о программе с ошибками
+pub fn errors(source: &str) -> String {
+    match transpile(source) {
+        Ok(code) => panic!("программа должна содержать ошибки, получен код:\n{}", code),
+        Err(messages) => messages,
+    }
+}
+
+// Трансляция, компиляция rustc и запуск программы
+fn execute(source: &str) -> Output {
+    let code = transpile(source).unwrap_or_else(|messages| panic!("ошибка трансляции:\n{}", messages));
+    let dir = work_dir();
+    let rust_file = dir.join("main.rs");
+    let executable = dir.join("main");
+    std::fs::write(&rust_file, &code).expect("не удалось записать код Rust");
+    let compiled = Command::new("rustc")
+        .args(["--edition", "2021", "-A", "warnings", "-o"])
+        .arg(&executable)
+        .arg(&rust_file)
+        .output()
+        .expect("не удалось запустить rustc");
+    assert!(
+        compiled.status.success(),
+        "rustc не скомпилировал результат:\n{}\n{}",
+        code,
+        String::from_utf8_lossy(&compiled.stderr)
+    );
+    Command::new(&executable).output().expect("не удалось запустить программу")
+}
+
+// Стандартный вывод успешно завершившейся программы
+pub fn run(source: &str) -> String {
+    let result = execute(source);
+    assert!(result.status.success(), "программа завершилась с ошибкой:\n{}", String::from_utf8_lossy(&result.stderr));
+    String::from_utf8_lossy(&result.stdout).into_owned()
+}
+
+// Сообщение программы, завершившейся с ошибкой
+pub fn run_failure(source: &str) -> String {
+    let result = execute(source);
+    assert!(!result.status.success(), "программа должна завершиться с ошибкой, вывод:\n{}", String::from_utf8_lossy(&result.stdout));
+    String::from_utf8_lossy(&result.stderr).into_owned()
+}
@@ -0,0 +1,26 @@
+// Сравнения и их цепочки
+mod common;
+
+use common::run;
+
+const TRACED: &str = "def f(x: int) -> int:\n    print(\"f\", x)\n    return x\n\n";
+
+// Цепочка раскрывается в попарные сравнения
+#[test]
+fn chained_comparison() {
+    assert_eq!(run("x: int = 5\nprint(0 < x < 10, 0 < x < 3, 1 <= 1 == 1)\n"), "True False True\n");
+}
+
+// Средний операнд вычисляется один раз
+#[test]
+fn middle_operand_is_evaluated_once() {
+    let source = format!("{}print(0 < f(5) < 10)\nl = [1]\nprint(0 < l.pop() < 10)\n", TRACED);
+    assert_eq!(run(&source), "f 5\nTrue\nTrue\n");
+}
+
+// Операнды вычисляются слева направо, а после ложного сравнения цепочка прерывается
+#[test]
+fn chain_keeps_order_and_short_circuits() {
+    let source = format!("{}print(f(1) < f(5) < f(10))\nprint(f(7) < f(5) < f(10))\nprint(0 < f(5) < f(3) < f(10))\n", TRACED);
+    assert_eq!(run(&source), "f 1\nf 5\nf 10\nTrue\nf 7\nf 5\nFalse\nf 5\nf 3\nFalse\n");
+}
@@ -0,0 +1,26 @@
+// Явные преобразования числовых типов
+mod common;
+
+use common::{run, run_failure};
+
+// Вещественное число в целое: дробная часть отбрасывается, как в Python
+#[test]
+fn float_to_int_truncates() {
+    let source = "x: float = -3.7\nprint(int(x), int(3.9), int64(2.5e9), uint8(200.9), uint8(-0.5))\n";
+    assert_eq!(run(source), "-3 3 2500000000 200 0\n");
+}
+
+// Значение вне диапазона целого типа не обрезается молча
+#[test]
+fn float_to_int_out_of_range() {
+    assert!(run_failure("x: double = -3.7\nprint(uint8(x))\n").contains("Значение -3.7 не помещается в тип uint8"));
+    assert!(run_failure("x: double = 1e300\nprint(int(x))\n").contains("не помещается в тип int"));
+    assert!(run_failure("x: double = 0.0\nprint(int64(x / x))\n").contains("NaN"));
+}
+
+// Сужение целых проверяется, расширение - нет
+#[test]
+fn integer_narrowing() {
+    assert_eq!(run("x: int = 200\nprint(uint8(x), int64(x))\n"), "200 200\n");
+    assert!(run_failure("x: int = 300\nprint(uint8(x))\n").contains("Значение не помещается в тип uint8"));
+}
@@ -0,0 +1,74 @@
+// Сообщения об ошибках: все ошибки файла за один запуск без каскадных повторов
+mod common;
+
+use common::{errors, json_messages};
+
+// Ошибки в разных строках сообщаются вместе
+#[test]
+fn all_errors_are_reported() {
+    let messages = errors("x: int = \"a\"\ny: bool = 1\nprint(z)\n");
+    assert!(messages.contains("Ошибок: 3"), "{}", messages);
+}
+
+// Переменная с ошибочным значением объявляется с указанным типом
+#[test]
+fn failed_declaration_still_declares_variable() {
+    let messages = errors("x: int = \"a\"\nif x > 1:\n    print(x)\n");
+    assert!(!messages.contains("не объявлена"), "{}", messages);
+    assert!(messages.contains("Ошибок: 1"), "{}", messages);
+}
+
+// Функция с ошибкой в заголовке объявляется, если сигнатуру удалось разобрать
+#[test]
+fn failed_def_header_still_declares_function() {
+    let messages = errors("def f(a: int, a: int) -> int:\n    return a\n\nprint(f(1, 2))\nprint(f(3, 4))\n");
+    assert!(messages.contains("Параметр 'a' указан несколько раз"), "{}", messages);
+    assert!(messages.contains("Ошибок: 1"), "{}", messages);
+    let messages = errors("def g(a: int) -> int\n    return a\nprint(g(1))\n");
+    assert!(messages.contains("Ошибок: 1"), "{}", messages);
+}
+
+// Ошибка указывает на подвыражение, а не на начало строки
+#[test]
+fn error_points_at_subexpression() {
+    let messages = errors("x: int = 1\ny: int = (x + 1) * (2 + \"a\")\n");
+    assert!(messages.contains("main.pd:2:23"), "{}", messages);
+}
+
+// Сообщение выводится, как в rustc: путь, строка исходника, подчёркивание и метки;
+// вне терминала - без цвета
+#[test]
+fn rendered_snippet() {
+    let messages = errors("z: int64 = 5\nw: int = z\n");
+    assert!(!messages.contains('\x1b'), "{}", messages);
+    let expected = "ошибка: Несовместимые типы: нельзя присвоить int64 в int\n --> ";
+    assert!(messages.contains(expected), "{}", messages);
+    let snippet = "main.pd:2:10\n  |\n2 | w: int = z\n  |    --- ожидаемый тип указан здесь\n  |          ^\n";
+    assert!(messages.contains(snippet), "{}", messages);
+}
+
+// Подсказки и примечания выводятся после фрагмента исходника
+#[test]
+fn help_and_note_lines() {
+    let messages = errors("if True\n    print(1)\n");
+    assert!(messages.contains("  = подсказка: "), "{}", messages);
+}
+
+// В формате JSON каждое сообщение - объект на отдельной строке с позицией и исправлениями
+#[test]
+fn json_format() {
+    let messages = json_messages("if True\n    print(1)\n");
+    assert_eq!(messages.len(), 1, "{:?}", messages);
+    assert!(messages[0].starts_with("{\"severity\":\"error\",\"code\":\"syntax\",\"message\":\"Ожидается ':' в конце заголовка 'if'\""), "{}", messages[0]);
+    assert!(messages[0].contains("\"start\":{\"line\":1,\"column\":8},\"end\":{\"line\":1,\"column\":9}"), "{}", messages[0]);
+    assert!(messages[0].contains("\"fixes\":[{\"message\":\"добавьте ':'\""), "{}", messages[0]);
+    assert!(messages[0].contains("\"replacement\":\":\"}]"), "{}", messages[0]);
+}
+
+// Предупреждения успешной трансляции тоже выводятся в JSON, без текста для человека
+#[test]
+fn json_warnings() {
+    let messages = json_messages("def f() -> int:\n    x: int = 1\n    return 2\n\nprint(f())\n");
+    assert_eq!(messages.len(), 1, "{:?}", messages);
+    assert!(messages[0].starts_with("{\"severity\":\"warning\""), "{}", messages[0]);
+}
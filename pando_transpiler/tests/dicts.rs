@@ -0,0 +1,55 @@
+// Словари: порядок добавления пар, поиск по ключу, изменение и удаление
+mod common;
+
+use common::{run, run_failure};
+
+// Повторный ключ литерала заменяет значение, но сохраняет позицию первой пары
+#[test]
+fn literal_with_repeated_key() {
+    assert_eq!(run("d: dict[str, int] = {\"a\": 1, \"b\": 2, \"a\": 3}\nprint(d, len(d))\n"), "{'a': 3, 'b': 2} 2\n");
+}
+
+// Новая пара добавляется в конец, удалённая - исчезает, повторно добавленная - снова в конце
+#[test]
+fn insertion_order_after_delete() {
+    let source = "d: dict[str, int] = {\"a\": 1, \"b\": 2}\nd[\"c\"] = 4\ndel d[\"a\"]\nd[\"a\"] = 5\nd[\"b\"] += 10\n\
+                  print(d, d[\"c\"], \"a\" in d, \"z\" not in d)\nfor k, v in d.items():\n    print(k, v)\n";
+    assert_eq!(run(source), "{'b': 12, 'c': 4, 'a': 5} 4 True True\nb 12\nc 4\na 5\n");
+}
+
+// Словари равны при одинаковых парах в любом порядке
+#[test]
+fn equality_ignores_order() {
+    let source = "d: dict[str, int] = {\"a\": 1, \"b\": 2}\nprint(d == {\"b\": 2, \"a\": 1}, d != {\"a\": 1}, d == {\"a\": 1, \"b\": 3})\n";
+    assert_eq!(run(source), "True True False\n");
+}
+
+// Значения-списки изменяются на месте через ключ
+#[test]
+fn nested_values() {
+    let source = "d: dict[int, list[int]] = {1: [1], 2: []}\nd[2].append(7)\nd[1][0] = 9\nprint(d, d.get(3, [0]))\n";
+    assert_eq!(run(source), "{1: [9], 2: [7]} [0]\n");
+}
+
+// Словарь с ключами str передаётся в функцию, возвращающую str
+#[test]
+fn dict_parameter_with_str_keys() {
+    let source = "def first(d: dict[str, int], default: str) -> str:\n    for k in d:\n        return k\n    return default\n\n\
+                  print(first({\"x\": 1}, \"-\"), first({}, \"-\"))\n";
+    assert_eq!(run(source), "x -\n");
+}
+
+// Большой словарь заполняется и читается без линейного поиска по ключу
+#[test]
+fn many_keys() {
+    let source = "squares: dict[int, int] = {}\nfor i in range(100000):\n    squares[i] = i % 1000\n\
+                  total: int = 0\nfor k in squares:\n    total += squares[k]\nprint(len(squares), total)\n";
+    assert_eq!(run(source), "100000 49950000\n");
+}
+
+// Отсутствующий ключ, как KeyError в Python, завершает программу
+#[test]
+fn missing_key() {
+    let messages = run_failure("d: dict[str, int] = {\"a\": 1}\nprint(d[\"b\"])\n");
+    assert!(messages.contains("Ключ \"b\" не найден в словаре"), "{}", messages);
+}
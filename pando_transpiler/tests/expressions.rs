@@ -0,0 +1,17 @@
+// Разбор и вычисление выражений: приоритеты, унарный минус, литералы
+mod common;
+
+use common::run;
+
+// Выражения, которые строковый парсер разбирал неверно
+#[test]
+fn former_misparses() {
+    let output = run("a: int = 5\nb: int = 3\nprint(a - -b, 2*-3, 10 - 2 - 3)\nprint(\"a+b\", 1e-5 * 1e5)\n");
+    assert_eq!(output, "8 -6 5\na+b 1.0\n");
+}
+
+// Степень правоассоциативна и связывает сильнее унарного минуса слева
+#[test]
+fn power_and_unary_minus() {
+    assert_eq!(run("print(-2 ** 2, 2 ** 3 ** 2, (1 + 2) * 3)\n"), "-4 512 9\n");
+}
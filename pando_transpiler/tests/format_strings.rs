@@ -0,0 +1,27 @@
+// f-строки со спецификаторами формата
+mod common;
+
+use common::{errors, run};
+
+// Ширина, выравнивание и точность
+#[test]
+fn width_align_precision() {
+    let source = "x: double = 3.14159\nname = \"ab\"\nprint(f\"[{x:.2f}] [{name:>4}] [{name:*<4}] [{7:03}]\")\n";
+    assert_eq!(run(source), "[3.14] [  ab] [ab**] [007]\n");
+}
+
+// Отрицательные числа в системах счисления выводятся знаком и модулем, как в Python
+#[test]
+fn signed_radix() {
+    let source = "n: int = -42\nm: int64 = 255\nu: uint8 = 200\n\
+                  print(f\"{n:#x}|{n:X}|{n:o}|{n:#b}|{n:08x}|{n:#010x}|{n:>8x}|{n:*^9x}|{m:+x}|{m:08b}|{u:#x}|{m:+08x}\")\n";
+    assert_eq!(run(source), "-0x2a|-2A|-52|-0b101010|-000002a|-0x000002a|     -2a|***-2a***|+ff|11111111|0xc8|+00000ff\n");
+    // У литерала тип в коде Rust не указан
+    assert_eq!(run("print(f\"{255:x}|{-255:x}|{-255:#06b}\")\n"), "ff|-ff|-0b11111111\n");
+}
+
+// Неподдерживаемый спецификатор - ошибка трансляции
+#[test]
+fn unsupported_spec() {
+    assert!(errors("x: int = 5\nprint(f\"{x:,}\")\n").contains("Разделитель разрядов ',' не поддерживается"));
+}
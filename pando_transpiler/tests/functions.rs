@@ -0,0 +1,30 @@
+// Функции: вызов до определения, рекурсия, проверка аргументов и результата
+mod common;
+
+use common::{errors, run};
+
+// Функцию можно вызвать выше её определения, в том числе рекурсивно
+#[test]
+fn call_before_definition() {
+    let source = "print(fact(5), greet(\"Ann\"))\n\ndef fact(n: int) -> int:\n    if n <= 1:\n        return 1\n    return n * fact(n - 1)\n\n\
+                  def greet(name: str) -> string:\n    return \"Hi, \" + name\n";
+    assert_eq!(run(source), "120 Hi, Ann\n");
+}
+
+// Функция без результата вызывается как инструкция
+#[test]
+fn none_function_as_statement() {
+    let source = "def show(x: int) -> None:\n    print(\"x =\", x)\n    return\n\nshow(3)\n";
+    assert_eq!(run(source), "x = 3\n");
+}
+
+// Типы аргументов, их число и тип результата проверяются
+#[test]
+fn argument_and_return_checks() {
+    let source = "def f(a: int) -> int:\n    return \"s\"\n\nprint(f(\"x\"))\nprint(f(1, 2))\ndef g() -> int:\n    print(1)\n";
+    let messages = errors(source);
+    assert!(messages.contains("Функция должна возвращать int, а возвращается str"), "{}", messages);
+    assert!(messages.contains("ожидается int, получен str"), "{}", messages);
+    assert!(messages.contains("Функция 'f' принимает 1 аргумент(ов), передано 2"), "{}", messages);
+    assert!(messages.contains("должна возвращать значение типа int на всех путях выполнения"), "{}", messages);
+}
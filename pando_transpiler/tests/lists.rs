@@ -0,0 +1,74 @@
+// Списки: литералы, методы, срезы и копирование при присваивании
+mod common;
+
+use common::{run, warnings};
+
+// Методы списка изменяют его на месте
+#[test]
+fn list_methods() {
+    let source = "a: list[int] = [3, 1, 2]\na.append(5)\na.insert(0, 7)\na.remove(1)\nprint(a, len(a), a[-1])\nprint(a.pop(), a)\n";
+    assert_eq!(run(source), "[7, 3, 2, 5] 4 5\n5 [7, 3, 2]\n");
+}
+
+// Элемент, прочитанный до изменения списка в том же print или f-строке, берётся до изменения
+#[test]
+fn read_before_mutation_in_one_call() {
+    let source = "a: list[int] = [1, 2, 3]\nprint(a[0], a.pop())\nprint(a[-1], a.append(4))\n\
+                  s = f\"{a[0]} {a.pop()} {a[1]:>3}\"\nprint(s, a)\n";
+    assert_eq!(run(source), "1 3\n2 None\n1 4   2 [1, 2]\n");
+}
+
+// Срез создаёт новый список
+#[test]
+fn list_slices() {
+    let source = "a: list[int] = [1, 2, 3, 4, 5]\nb = a[1:3]\nb.append(0)\nprint(a[:2], a[-2:], b)\n";
+    assert_eq!(run(source), "[1, 2] [4, 5] [2, 3, 0]\n");
+}
+
+// Присваивание копирует список: об изменении копии сообщается предупреждением
+#[test]
+fn changed_copy_is_reported() {
+    let source = "a: list[int] = [1, 2]\nb = a\nb.append(9)\nprint(a, b)\n";
+    let messages = warnings(source);
+    assert!(messages.contains("'b' получает копию коллекции"), "{}", messages);
+    assert!(messages.contains("b = a[:]"), "{}", messages);
+    assert_eq!(run(source), "[1, 2] [1, 2, 9]\n");
+}
+
+// Изменение исходного списка после копирования тоже расходится с Python
+#[test]
+fn changed_source_is_reported() {
+    let messages = warnings("a: list[int] = [1, 2]\nb = a\na[0] = 5\nprint(a, b)\n");
+    assert!(messages.contains("изменение здесь не отразится на 'b'"), "{}", messages);
+}
+
+// Явная копия, копия без изменений и переприсваивание перед изменением не сообщаются
+#[test]
+fn unchanged_copies_are_not_reported() {
+    for source in [
+        "a: list[int] = [1, 2]\nb = a[:]\nb.append(9)\nprint(a, b)\n",
+        "a: list[int] = [1, 2]\nb = a\nprint(a, b)\n",
+        "a: list[int] = [1, 2]\nb = a\nb = [3]\nb.append(9)\nprint(a, b)\n",
+    ] {
+        let messages = warnings(source);
+        assert!(!messages.contains("копию"), "{}", messages);
+    }
+}
+
+// Изменение параметра-списка не видно вызывающему коду
+#[test]
+fn changed_parameter_is_reported() {
+    let source = "def add(xs: list[int]) -> None:\n    xs.append(1)\n\na: list[int] = []\nadd(a)\nprint(a)\n";
+    let messages = warnings(source);
+    assert!(messages.contains("Изменение параметра 'xs' не видно вызывающему коду"), "{}", messages);
+    assert!(messages.contains("присвойте его: xs = add(xs)"), "{}", messages);
+    assert_eq!(run(source), "[]\n");
+}
+
+// Переменная цикла по списку списков получает копию элемента
+#[test]
+fn changed_loop_element_is_reported() {
+    let source = "grid: list[list[int]] = [[1], [2]]\nfor row in grid:\n    row.append(0)\nprint(grid)\n";
+    let messages = warnings(source);
+    assert!(messages.contains("Переменная цикла 'row' получает копию элемента"), "{}", messages);
+}
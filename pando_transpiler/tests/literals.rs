@@ -0,0 +1,41 @@
+// Типизация числовых литералов и константных выражений по контексту
+mod common;
+
+use common::{errors, run};
+
+// Литерал принимает тип объявления, а константа, не поместившаяся в int, - более широкий тип
+#[test]
+fn literal_takes_declared_type() {
+    let source = "a: int64 = 5000000000\nb: double = 1.5\nc: int64 = 1 << 40\nd = 2 ** 31\ne: int = -2147483648\nprint(a, b, c, d, e)\n";
+    assert_eq!(run(source), "5000000000 1.5 1099511627776 2147483648 -2147483648\n");
+}
+
+// Значение константного выражения проверяется целиком, а не по отдельным литералам
+#[test]
+fn constant_expression_out_of_range() {
+    assert!(errors("x: uint8 = 255 + 1\nprint(x)\n").contains("Значение 256 не помещается в тип uint8"));
+    assert!(errors("x: int = 1 << 40\nprint(x)\n").contains("Сдвиг на 40 бит недопустим для типа int"));
+    assert!(errors("x: int = 3000000000 - 1\nprint(x)\n").contains("не помещается в тип int"));
+    assert!(errors("x = 1e999\nprint(x)\n").contains("Значение 1e999 не помещается в тип double"));
+    assert!(errors("x: float = 1e300\nprint(x)\n").contains("Значение 1e300 не помещается в тип float"));
+}
+
+// Деление на ноль в константе - ошибка трансляции
+#[test]
+fn constant_division_by_zero() {
+    assert!(errors("x = 1 // 0\nprint(x)\n").contains("Деление на ноль"));
+    assert!(errors("x: double = 1.5 % 0.0\nprint(x)\n").contains("Деление на ноль"));
+}
+
+// Шаг range не приводится к типу переменной цикла
+#[test]
+fn range_step_keeps_its_sign() {
+    assert_eq!(run("u: uint8 = 3\nfor i in range(u, 0, -1):\n    print(i)\n"), "3\n2\n1\n");
+}
+
+// Литерал str в контексте string копируется в строку
+#[test]
+fn str_literal_as_string() {
+    let source = "def greet(name: string) -> string:\n    return \"hi \" + name\n\ns: string = \"ab\"\ns += \"c\"\nprint(s, greet(\"bob\"), s == \"abc\")\n";
+    assert_eq!(run(source), "abc hi bob True\n");
+}
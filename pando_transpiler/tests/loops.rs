@@ -0,0 +1,61 @@
+// Циклы while и for: break, continue и ветка else
+mod common;
+
+use common::{errors, run};
+
+// continue переходит к следующей итерации, break завершает цикл
+#[test]
+fn while_with_break_and_continue() {
+    let source = "i: int = 0\ntotal: int = 0\nwhile i < 10:\n    i += 1\n    if i % 2 == 0:\n        continue\n\
+                  \x20   if i > 7:\n        break\n    total += i\nprint(i, total)\n";
+    assert_eq!(run(source), "9 16\n");
+}
+
+// Ветка else выполняется, только если цикл завершился без break
+#[test]
+fn while_else() {
+    let source = "n: int = 3\nwhile n > 0:\n    n -= 1\nelse:\n    print(\"done\", n)\n\
+                  while True:\n    break\nelse:\n    print(\"unreachable\")\n";
+    assert_eq!(run(source), "done 0\n");
+}
+
+// break и continue вне цикла - ошибки с позицией инструкции
+#[test]
+fn break_outside_loop() {
+    let messages = errors("x: int = 1\nbreak\nif x > 0:\n    continue\n");
+    assert!(messages.contains("'break' вне цикла\n --> "), "{}", messages);
+    assert!(messages.contains("main.pd:2:1"), "{}", messages);
+    assert!(messages.contains("'continue' вне цикла"), "{}", messages);
+    assert!(messages.contains("main.pd:4:5"), "{}", messages);
+}
+
+// range с одним, двумя и тремя аргументами, в том числе с отрицательным шагом
+#[test]
+fn range_forms() {
+    let source = "for i in range(3):\n    print(i, end=\" \")\nfor i in range(2, 5):\n    print(i, end=\" \")\n\
+                  for i in range(10, 0, -3):\n    print(i, end=\" \")\nfor i in range(0, 10, 4):\n    print(i, end=\" \")\n";
+    assert_eq!(run(source), "0 1 2 2 3 4 10 7 4 1 0 4 8 ");
+}
+
+// Переменная цикла получает тип границ range
+#[test]
+fn range_variable_type() {
+    let source = "n: int64 = 3\nfor j in range(n):\n    print(j * 3000000000, end=\" \")\n";
+    assert_eq!(run(source), "0 3000000000 6000000000 ");
+}
+
+// Строка перебирается по символам, байты - по значениям байтов; else выполняется без break
+#[test]
+fn iterate_text_and_bytes() {
+    let source = "for c in \"héj\":\n    print(c, end=\",\")\nfor b in b\"AZ\":\n    print(b, end=\",\")\n\
+                  for i in range(3):\n    if i == 5:\n        break\nelse:\n    print(\"no break\")\n";
+    assert_eq!(run(source), "h,é,j,65,90,no break\n");
+}
+
+// Нулевой шаг и перебор числа - ошибки трансляции
+#[test]
+fn invalid_iterables() {
+    let messages = errors("for i in range(0, 5, 0):\n    print(i)\nfor x in 5:\n    print(x)\n");
+    assert!(messages.contains("Шаг range не может быть равен нулю"), "{}", messages);
+    assert!(messages.contains("Тип int не поддерживает итерацию"), "{}", messages);
+}
@@ -0,0 +1,24 @@
+// Вывод значений print в том виде, в каком их выводит Python
+mod common;
+
+use common::run;
+
+// Несколько аргументов, sep и end
+#[test]
+fn print_sep_and_end() {
+    assert_eq!(run("print(1, \"a\", True, sep=\", \", end=\"!\\n\")\nprint()\n"), "1, a, True!\n\n");
+}
+
+// Вещественные числа: дробная часть и экспонента со знаком и двумя цифрами
+#[test]
+fn float_repr() {
+    let source = "x: double = 1e20\nprint(1e20, 1e-5, x, 1.5, 1e16, 1e15, 0.0001, -2.5e-10)\nl: list[double] = [1e20, 2.0]\nprint(l, f\"{x}\")\n";
+    assert_eq!(run(source), "1e+20 1e-05 1e+20 1.5 1e+16 1000000000000000.0 0.0001 -2.5e-10\n[1e+20, 2.0] 1e+20\n");
+}
+
+// Байты: кавычки выбираются, как в Python, и лишнее экранирование снимается
+#[test]
+fn bytes_repr() {
+    let source = "print(b\"a\\\"b\", b\"it's\", b\"both'\\\"\", bytearray(b\"x'y\"), b\"\\\\\\\"\", b\"\\n\\x00\")\n";
+    assert_eq!(run(source), "b'a\"b' b\"it's\" b'both\\'\"' bytearray(b\"x\\'y\") b'\\\\\"' b'\\n\\x00'\n");
+}
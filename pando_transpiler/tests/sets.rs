@@ -0,0 +1,27 @@
+// Множества: литералы, add/discard, in и операции над множествами
+mod common;
+
+use common::{errors, run};
+
+// Повторы в литерале отбрасываются; discard отсутствующего элемента не ошибка
+#[test]
+fn set_methods() {
+    let source = "a: set[int] = {3, 1, 2, 3}\na.add(5)\na.discard(1)\na.discard(42)\nprint(a, len(a), 2 in a, 7 not in a)\n";
+    assert_eq!(run(source), "{2, 3, 5} 3 True True\n");
+}
+
+// |, &, ^ и - над множествами - объединение, пересечение, симметрическая разность и разность
+#[test]
+fn set_algebra() {
+    let source = "a: set[int] = {2, 3, 5}\nb: set[int] = {2, 3, 4}\nprint(a | b, a & b, a ^ b, a - b)\n\
+                  e: set[str] = set()\nprint(e, set([1, 1, 2]))\na |= b\nprint(a)\n";
+    assert_eq!(run(source), "{2, 3, 4, 5} {2, 3} {4, 5} {5}\nset() {1, 2}\n{2, 3, 4, 5}\n");
+}
+
+// Операнды должны быть множествами одного типа, а элементы - хешируемыми
+#[test]
+fn set_type_errors() {
+    let messages = errors("a: set[int] = {1}\nb: set[str] = {\"a\"}\nc = a | b\nd: set[list[int]] = set()\n");
+    assert!(messages.contains("Несовместимые типы в операции: set[int] и set[str]"), "{}", messages);
+    assert!(messages.contains("Тип list[int] не может быть элементом множества"), "{}", messages);
+}
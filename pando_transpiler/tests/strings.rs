@@ -0,0 +1,35 @@
+// Строки: сложение, повторение, методы, индексы и срезы по символам
+mod common;
+
+use common::{errors, run};
+
+// Индексы, срезы и длина считаются в символах, а не в байтах UTF-8
+#[test]
+fn characters_not_bytes() {
+    let source = "s: str = \"  Hello, Wörld  \"\nt: string = s.strip()\nprint(t.upper(), t.lower(), len(t), t[1], t[-1], t[7:], t[:5], t.find(\"ö\"))\n";
+    assert_eq!(run(source), "HELLO, WÖRLD hello, wörld 12 e d Wörld Hello 8\n");
+}
+
+// split, join, replace, startswith, endswith
+#[test]
+fn string_methods() {
+    let source = "parts: list[string] = \"a,b,c\".split(\",\")\nprint(parts, \"-\".join(parts), \"x y  z\".split())\n\
+                  t: string = \"Hello\"\nprint(t.replace(\"l\", \"L\"), t.startswith(\"He\"), t.endswith(\"x\"), t.find(\"zz\"))\n";
+    assert_eq!(run(source), "['a', 'b', 'c'] a-b-c ['x', 'y', 'z']\nHeLLo True False -1\n");
+}
+
+// Сложение и повторение; отрицательное число повторений даёт пустую строку
+#[test]
+fn concatenation_and_repetition() {
+    let source = "r: string = \"ab\" * 3 + \"!\"\nr += \"?\"\nprint(r, -1 * \"x\" == \"\")\n";
+    assert_eq!(run(source), "ababab!? True\n");
+}
+
+// Дополнять можно только string; len и операторы проверяют тип
+#[test]
+fn string_errors() {
+    let messages = errors("s: str = \"a\"\ns += \"b\"\nx: int = len(5)\n");
+    assert!(messages.contains("Операция += недопустима для типа str"), "{}", messages);
+    assert!(messages.contains("объявите переменную как имя: string"), "{}", messages);
+    assert!(messages.contains("Функция len недопустима для типа int"), "{}", messages);
+}
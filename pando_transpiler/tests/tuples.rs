@@ -0,0 +1,28 @@
+// Кортежи: литералы, индексы и распаковка
+mod common;
+
+use common::{errors, run};
+
+// Кортеж из одного элемента выводится с запятой; отрицательный индекс считается с конца
+#[test]
+fn tuple_literals_and_indexing() {
+    let source = "p: tuple[int, str] = (1, \"a\")\none: tuple[int] = (5,)\nprint(p, one, p[0], p[1], p[-1], p == (1, \"a\"))\n";
+    assert_eq!(run(source), "(1, 'a') (5,) 1 a a True\n");
+}
+
+// Обмен значений, распаковка кортежа и переменные цикла по списку кортежей
+#[test]
+fn destructuring() {
+    let source = "a: int = 1\nb: int = 2\na, b = b, a\nprint(a, b)\np: tuple[int, str] = (1, \"a\")\nx, y = p\nprint(x, y)\n\
+                  pairs: list[tuple[int, int]] = [(1, 2), (3, 4)]\nfor l, r in pairs:\n    print(l + r, end=\" \")\n";
+    assert_eq!(run(source), "2 1\n1 a\n3 7 ");
+}
+
+// Индекс за границами, запись в элемент и распаковка в другое число переменных - ошибки
+#[test]
+fn tuple_errors() {
+    let messages = errors("p: tuple[int, str] = (1, \"a\")\nprint(p[2])\np[0] = 3\nx, y, z = p\n");
+    assert!(messages.contains("Индекс 2 вне границ кортежа из 2 элемент(ов)"), "{}", messages);
+    assert!(messages.contains("Элементу кортежа нельзя присвоить значение"), "{}", messages);
+    assert!(messages.contains("Нельзя распаковать tuple[int, str] в 3 переменные"), "{}", messages);
+}
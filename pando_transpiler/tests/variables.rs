@@ -0,0 +1,84 @@
+// Переменные: вывод типа, mut только у переприсваиваемых, области видимости
+mod common;
+
+use common::{errors, run, transpile, warnings};
+
+// Первое присваивание объявляет переменную с типом значения
+#[test]
+fn inferred_declarations() {
+    let source = "x = 5\ny = 2.5\ns = \"hi\"\nt = s + \"!\"\nbig = 3000000000\nl = [1, 2]\nx = 7\nprint(x, y, s, t, big, l)\n";
+    let code = transpile(source).unwrap();
+    assert!(code.contains("let t: String = "), "{}", code);
+    assert!(code.contains("let big: i64 = 3000000000;"), "{}", code);
+    assert_eq!(run(source), "7 2.5 hi hi! 3000000000 [1, 2]\n");
+}
+
+// Следующее присваивание должно совпадать с выведенным типом; тип пустого списка не выводится
+#[test]
+fn inference_errors() {
+    let messages = errors("x = 5\nx = \"a\"\nz = []\n");
+    assert!(messages.contains("нельзя присвоить str в int"), "{}", messages);
+    assert!(messages.contains("Невозможно вывести тип переменной 'z' из пустого списка"), "{}", messages);
+    assert!(messages.contains("укажите тип: z: list[int] = []"), "{}", messages);
+}
+
+// mut получают только переприсваиваемые и изменяемые на месте переменные и параметры
+#[test]
+fn mut_only_when_needed() {
+    let source = "const LIMIT: int = 3\na: int = 1\nb: int = 2\nb = 3\nitems: list[int] = []\nitems.append(LIMIT)\n\
+                  def bump(n: int) -> int:\n    n += 1\n    return n\n\nprint(a, b, items, bump(LIMIT))\n";
+    let code = transpile(source).unwrap();
+    assert!(code.contains("const LIMIT: i32 = 3;"), "{}", code);
+    assert!(code.contains("let a: i32 = 1;"), "{}", code);
+    assert!(code.contains("let mut b: i32 = 2;"), "{}", code);
+    assert!(code.contains("let mut items: Vec<i32>"), "{}", code);
+    assert!(code.contains("fn bump(mut n: i32) -> i32"), "{}", code);
+    assert_eq!(run(source), "1 3 [3] 4\n");
+}
+
+// Константу нельзя изменить
+#[test]
+fn constant_is_immutable() {
+    let messages = errors("const X: int = 1\nX = 2\n");
+    assert!(messages.contains("Нельзя изменить константу 'X'"), "{}", messages);
+    assert!(messages.contains("константа объявлена здесь"), "{}", messages);
+}
+
+// Переменная блока не видна после него; повторное объявление - ошибка
+#[test]
+fn block_scope_and_redeclaration() {
+    let messages = errors("x: int = 1\nif x > 0:\n    inner: int = 2\n    print(inner)\nprint(inner)\nx: string = \"a\"\n");
+    assert!(messages.contains("Переменная 'inner' не объявлена\n --> "), "{}", messages);
+    assert!(messages.contains("main.pd:5:7"), "{}", messages);
+    assert!(messages.contains("Переменная 'x' уже объявлена"), "{}", messages);
+    assert!(messages.contains("первое объявление здесь"), "{}", messages);
+}
+
+// Чтение до присваивания, в том числе присваивания не на всех путях, - ошибка;
+// неиспользуемая переменная - предупреждение
+#[test]
+fn use_before_assignment_and_unused() {
+    let messages = errors("x: int = 1\ny: int\nprint(y)\nz: int\nif x > 0:\n    z = 1\nprint(z)\nunused: int = 5\n");
+    assert!(messages.contains("Переменная 'y' используется до присваивания значения"), "{}", messages);
+    assert!(messages.contains("значение присваивается не на всех путях выполнения"), "{}", messages);
+    assert!(messages.contains("предупреждение: Переменная 'unused' не используется"), "{}", messages);
+    assert!(messages.contains("Ошибок: 2, предупреждений: 1"), "{}", messages);
+}
+
+// Предупреждение указывает на само имя переменной цикла, распаковки или параметра
+#[test]
+fn unused_names_point_at_the_name() {
+    let source = "for i in range(5, 0):\n    pass\na, b = 1, 2\nprint(a)\ndef f(xs: list[int]) -> None:\n    xs.append(1)\n\nf([1])\n";
+    let messages = warnings(source);
+    assert!(messages.contains("main.pd:1:5\n  |\n1 | for i in range(5, 0):\n  |     ^\n"), "{}", messages);
+    assert!(messages.contains("main.pd:3:4\n"), "{}", messages);
+    assert!(messages.contains("main.pd:5:7\n"), "{}", messages);
+}
+
+// Присваивание во всех ветках if/else инициализирует переменную; имя с _ не сообщается
+#[test]
+fn assigned_on_all_paths() {
+    let source = "y: int\nx: int = 2\nif x > 1:\n    y = 1\nelse:\n    y = 2\nprint(y)\n_skip: int = 0\n";
+    assert_eq!(warnings(source), "");
+    assert_eq!(run(source), "1\n");
+}